@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use quilt_installer::config::{ClientConfig, Config, ModConfig};
+use quilt_installer::installer::{self, ClientInstallation, MinecraftVersion};
+use quilt_installer::{fixups, modrinth, mods, mojang};
+use reqwest::Client;
+
+use crate::cli;
+
+/// What `apply` changed and what was already up to date
+#[derive(Debug, Default)]
+struct Report {
+    changed: Vec<String>,
+    unchanged: Vec<String>,
+}
+
+impl Report {
+    fn record(&mut self, changed: bool, what: String) {
+        if changed {
+            self.changed.push(what);
+        } else {
+            self.unchanged.push(what);
+        }
+    }
+}
+
+/// Brings every client installation described in the config file up to date,
+/// leaving those that already are untouched
+pub async fn run(client: Client, path: &Path) -> Result<()> {
+    let config = Config::read(path)?;
+    let mut report = Report::default();
+    for client_config in &config.clients {
+        apply_client(&client, client_config, &mut report).await?;
+    }
+
+    for what in &report.changed {
+        println!("changed    {what}");
+    }
+    for what in &report.unchanged {
+        println!("unchanged  {what}");
+    }
+    println!(
+        "{} changed, {} already up to date",
+        report.changed.len(),
+        report.unchanged.len()
+    );
+    Ok(())
+}
+
+async fn apply_client(client: &Client, config: &ClientConfig, report: &mut Report) -> Result<()> {
+    for name in &config.disabled_fixups {
+        if !fixups::FIXUPS.iter().any(|f| f.name == name) {
+            bail!("Unknown fixup {name} in disabled_fixups");
+        }
+    }
+    let install_dir = config
+        .install_dir
+        .clone()
+        .unwrap_or_else(installer::get_default_client_directory);
+    let mods_dir = install_dir.join("mods");
+    let (minecraft_version, loader_version) = cli::get_versions(
+        client.clone(),
+        config.minecraft_version.clone().into(),
+        config.loader_version.clone().into(),
+        config.loader_channel,
+        None,
+        mojang::VERSION_MANIFEST_URL,
+        &mods_dir,
+    )
+    .await?;
+
+    let installation = ClientInstallation {
+        minecraft_version: minecraft_version.clone(),
+        loader_version,
+        install_dir,
+        generate_profile: config.generate_profile,
+        download_libraries: config.download_libraries,
+        disabled_fixups: config.disabled_fixups.clone(),
+        pinned_artifacts: Vec::new(),
+    };
+    let what = format!("client {installation}");
+    let changed = !installer::is_client_installed(client, &installation).await?;
+    if changed {
+        installer::install_client(client.clone(), installation).await?;
+    }
+    report.record(changed, what);
+
+    apply_mods(
+        client,
+        config.qfapi,
+        &config.mods,
+        &minecraft_version,
+        &mods_dir,
+        report,
+    )
+    .await
+}
+
+async fn apply_mods(
+    client: &Client,
+    qfapi: bool,
+    mods: &[ModConfig],
+    minecraft_version: &MinecraftVersion,
+    mods_dir: &Path,
+    report: &mut Report,
+) -> Result<()> {
+    if qfapi {
+        let artifact =
+            modrinth::find_qfapi(client.clone(), modrinth::DEFAULT_API_URL, minecraft_version)
+                .await?;
+        let changed = !mods::is_mod_installed(&artifact, mods_dir);
+        if changed {
+            modrinth::install_locked_qfapi(client.clone(), &artifact, mods_dir).await?;
+        }
+        report.record(
+            changed,
+            format!("mod {} in {}", artifact.name, mods_dir.display()),
+        );
+    }
+
+    for config in mods {
+        let artifact = config.artifact();
+        let changed = !mods::is_mod_installed(&artifact, mods_dir);
+        if changed {
+            mods::install_mod(client, &artifact, mods_dir).await?;
+        }
+        report.record(
+            changed,
+            format!("mod {} in {}", artifact.name, mods_dir.display()),
+        );
+    }
+    Ok(())
+}
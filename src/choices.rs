@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use quilt_installer::installer::get_default_client_directory;
+use quilt_installer::{
+    install_client, install_server, modrinth, ClientInstallation, Installation, LoaderChannel,
+    LoaderVersion, MinecraftVersion, ServerInstallation,
+};
+use reqwest::Client;
+
+/// What to install, as picked in the GUI or the wizard
+#[derive(Debug, Clone)]
+pub struct Choices {
+    // Minecraft version picker
+    pub minecraft_versions: Vec<MinecraftVersion>,
+    pub selected_minecraft_version: Option<MinecraftVersion>,
+    pub show_snapshots: bool,
+
+    // Quilt Loader version picker
+    pub loader_versions: Vec<LoaderVersion>,
+    pub selected_loader_version: Option<LoaderVersion>,
+    /// The least stable channel to offer
+    pub loader_channel: LoaderChannel,
+
+    pub installation_type: Installation,
+
+    // Client settings
+    pub client_location: PathBuf,
+    pub generate_profile: bool,
+    pub download_libraries: bool,
+
+    // Mod settings
+    pub install_qfapi: bool,
+
+    // Server settings
+    pub server_location: PathBuf,
+    pub download_server_jar: bool,
+    pub generate_launch_script: bool,
+}
+
+impl Default for Choices {
+    fn default() -> Self {
+        Self {
+            minecraft_versions: Vec::new(),
+            selected_minecraft_version: None,
+            show_snapshots: false,
+            loader_versions: Vec::new(),
+            selected_loader_version: None,
+            loader_channel: LoaderChannel::Stable,
+            installation_type: Installation::Client,
+            client_location: get_default_client_directory(),
+            generate_profile: true,
+            download_libraries: false,
+            install_qfapi: false,
+            server_location: std::env::current_dir().unwrap_or_default(),
+            download_server_jar: true,
+            generate_launch_script: true,
+        }
+    }
+}
+
+impl Choices {
+    /// The Minecraft versions to offer, hiding snapshots unless enabled
+    pub fn visible_minecraft_versions(&self) -> impl Iterator<Item = &MinecraftVersion> {
+        self.minecraft_versions
+            .iter()
+            .filter(|v| self.show_snapshots || v.stable)
+    }
+
+    /// The Quilt Loader versions to offer, hiding those less stable than the chosen channel
+    pub fn visible_loader_versions(&self) -> impl Iterator<Item = &LoaderVersion> {
+        self.loader_versions
+            .iter()
+            .filter(|v| v.channel() >= self.loader_channel)
+    }
+
+    pub fn set_minecraft_versions(&mut self, versions: Vec<MinecraftVersion>) {
+        self.minecraft_versions = versions;
+        if self.selected_minecraft_version.is_none() {
+            let newest = self.visible_minecraft_versions().next().cloned();
+            self.selected_minecraft_version = newest;
+        }
+    }
+
+    /// Replaces the loader versions with those compatible with the selected Minecraft version,
+    /// keeping the selected loader version if it is still among them
+    pub fn set_loader_versions(&mut self, versions: Vec<LoaderVersion>) {
+        self.loader_versions = versions;
+        if !self
+            .selected_loader_version
+            .as_ref()
+            .is_some_and(|v| self.loader_versions.contains(v))
+        {
+            let newest = self.visible_loader_versions().next().cloned();
+            self.selected_loader_version = newest;
+        }
+    }
+
+    /// Toggles snapshots, selecting the newest version that is now visible
+    pub fn set_show_snapshots(&mut self, enable: bool) {
+        self.show_snapshots = enable;
+        let newest = self.visible_minecraft_versions().next().cloned();
+        self.selected_minecraft_version = newest;
+    }
+
+    /// Changes the channel, selecting the newest version that is now visible
+    pub fn set_loader_channel(&mut self, channel: LoaderChannel) {
+        self.loader_channel = channel;
+        let newest = self.visible_loader_versions().next().cloned();
+        self.selected_loader_version = newest;
+    }
+
+    pub fn install_dir(&self) -> &Path {
+        match self.installation_type {
+            Installation::Client => &self.client_location,
+            Installation::Server => &self.server_location,
+        }
+    }
+
+    pub fn set_install_dir(&mut self, dir: PathBuf) {
+        match self.installation_type {
+            Installation::Client => self.client_location = dir,
+            Installation::Server => self.server_location = dir,
+        }
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.install_dir().join("mods")
+    }
+
+    pub fn selected_versions(&self) -> Result<(MinecraftVersion, LoaderVersion)> {
+        let Some(minecraft_version) = self.selected_minecraft_version.clone() else {
+            return Err(anyhow!("Minecraft version not selected!"));
+        };
+        let Some(loader_version) = self.selected_loader_version.clone() else {
+            return Err(anyhow!("Loader version not selected!"));
+        };
+        Ok((minecraft_version, loader_version))
+    }
+
+    /// Installs what was picked, followed by Quilted Fabric API if wanted
+    pub async fn install(self, client: Client) -> Result<()> {
+        let (minecraft_version, loader_version) = self.selected_versions()?;
+        let mods_dir = self.mods_dir();
+
+        match self.installation_type {
+            Installation::Client => {
+                install_client(
+                    client.clone(),
+                    ClientInstallation {
+                        minecraft_version: minecraft_version.clone(),
+                        loader_version,
+                        install_dir: self.client_location,
+                        generate_profile: self.generate_profile,
+                        download_libraries: self.download_libraries,
+                        disabled_fixups: Vec::new(),
+                        pinned_artifacts: Vec::new(),
+                    },
+                )
+                .await?;
+            }
+            Installation::Server => {
+                install_server(
+                    client.clone(),
+                    ServerInstallation {
+                        minecraft_version: minecraft_version.clone(),
+                        loader_version,
+                        install_dir: self.server_location,
+                        download_jar: self.download_server_jar,
+                        generate_script: self.generate_launch_script,
+                    },
+                )
+                .await?;
+            }
+        }
+
+        if self.install_qfapi {
+            modrinth::install_qfapi(
+                client,
+                modrinth::DEFAULT_API_URL,
+                &minecraft_version,
+                &mods_dir,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// The CLI invocation that installs the same thing without asking
+    pub fn command_line(&self) -> Result<String> {
+        let (minecraft_version, loader_version) = self.selected_versions()?;
+        let mut args = vec![
+            env!("CARGO_PKG_NAME").to_owned(),
+            "--minecraft-version".into(),
+            minecraft_version.version,
+            "--loader-version".into(),
+            loader_version.to_string(),
+        ];
+        match self.installation_type {
+            Installation::Client => {
+                args.push("client".into());
+                if !self.generate_profile {
+                    args.push("--no-profile".into());
+                }
+                if self.download_libraries {
+                    args.push("--download-libraries".into());
+                }
+            }
+            Installation::Server => {
+                args.push("server".into());
+                if !self.download_server_jar {
+                    args.push("--no-jar".into());
+                }
+                if !self.generate_launch_script {
+                    args.push("--no-launch-script".into());
+                }
+            }
+        }
+        if self.install_qfapi {
+            args.push("--qfapi".into());
+        }
+        args.push("--install-dir".into());
+        args.push(self.install_dir().display().to_string());
+
+        Ok(shell_words::join(args))
+    }
+}
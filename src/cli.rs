@@ -1,15 +1,61 @@
-use crate::installer::{
-    self, ClientInstallation, LoaderVersion, MinecraftVersion, ServerInstallation,
-};
-use anyhow::Context;
+use crate::{apply, wizard};
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{bail, Context};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::parser::ValueSource;
+use clap::{
+    builder::PossibleValuesParser, Arg, ArgMatches, Command, CommandFactory, FromArgMatches,
+    Parser, Subcommand, ValueEnum,
+};
 use derive_more::Display;
+use quilt_installer::config;
+use quilt_installer::curseforge::{self, CurseForgeApi, ModpackInstallation};
+use quilt_installer::fixups;
+use quilt_installer::installer::{
+    self, ClientInstallation, Installation, LoaderChannel, LoaderVersion, MinecraftVersion,
+    ServerInstallation,
+};
+use quilt_installer::lock::{ArtifactKind, LockedOptions, Lockfile};
+use quilt_installer::modrinth;
+use quilt_installer::mods;
+use quilt_installer::mojang::{self, ReleaseType};
+use quilt_installer::packwiz::{self, PackInstallation, PackLocation};
+use quilt_installer::verify;
+use quilt_installer::{suggest_versions, InstallError};
+use regex::Regex;
 use reqwest::Client;
-use std::path::PathBuf;
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// The prefix of the environment variables that can set any option
+const ENV_PREFIX: &str = "QUILT_INSTALLER_";
+
+const AFTER_HELP: &str = "\
+Environment:
+  Every option can also be set with a QUILT_INSTALLER_<OPTION> environment variable,
+  like QUILT_INSTALLER_LOADER_CHANNEL=rc or QUILT_INSTALLER_DOWNLOAD_LIBRARIES=true.
+  Options on the command line take precedence over environment variables,
+  which take precedence over the defaults.
+  The config file of `apply` describes its installations completely,
+  so the version options do not apply to it.
+
+Exit codes:
+  1   other failure
+  2   invalid arguments
+  3   network error
+  4   version or file not found on the meta servers
+  5   invalid installation directory
+  6   corrupt profile
+  7   file system error
+  8   checksum mismatch
+  9   the Quilt Loader version is not available for the Minecraft version
+  10  unsupported kind of installation
+  11  invalid lockfile
+  12  artifact missing from the lockfile";
 
 #[derive(Parser)]
-#[command(about, version, propagate_version = true)]
+#[command(about, version, propagate_version = true, after_help = AFTER_HELP)]
 pub struct Args {
     #[clap(subcommand)]
     pub subcommand: Option<Subcommands>,
@@ -18,17 +64,41 @@ pub struct Args {
     /// Pick between the
     /// latest `stable` version,
     /// latest `snapshot`,
+    /// the `latest` version of any kind,
+    /// the latest stable version of a series like `1.20.x`,
     /// or a specific version number.
     #[arg(short = 'm', long, default_value_t)]
     minecraft_version: MCVersionCLI,
     /// The Quilt loader version to install
     ///
     /// Pick between the
-    /// latest `stable` version,
+    /// latest `stable` version (or the latest as stable as `--loader-channel`),
     /// latest `beta`,
+    /// the `latest` version of any kind,
+    /// the newest version the installed mods allow (`auto`),
+    /// the newest stable version matching a requirement like `>=0.20, <0.21` or `0.19.*`,
     /// or a specific version number.
     #[arg(short = 'l', long, default_value_t)]
     loader_version: LoaderVersionCLI,
+    /// The least stable Quilt Loader channel that `stable`, `auto` and requirements may pick,
+    /// and that `versions loader` lists
+    ///
+    /// From most to least stable: stable, rc, pre, beta or alpha.
+    /// Defaults to stable, and to listing every channel.
+    #[arg(long, global = true, value_name = "CHANNEL", value_parser = str::parse::<LoaderChannel>)]
+    loader_channel: Option<LoaderChannel>,
+    /// Resolve `stable`, `snapshot`, `beta`, `latest` and series like `1.20.x`
+    /// to the versions that were newest at a date like `2024-03-01`, or an RFC 3339 time
+    #[arg(long, value_name = "DATE", value_parser = parse_as_of)]
+    as_of: Option<DateTime<Utc>>,
+    /// Mojang's version manifest, for the release dates `--as-of` compares against,
+    /// the release types, dates and Java versions `versions minecraft` lists,
+    /// and the release details shown in the GUI
+    #[arg(long, global = true, value_name = "URL", default_value = mojang::VERSION_MANIFEST_URL)]
+    pub manifest_url: String,
+    /// Print more information about what is being done
+    #[arg(short = 'v', long, global = true)]
+    pub verbose: bool,
 }
 
 #[derive(Subcommand)]
@@ -38,6 +108,39 @@ pub enum Subcommands {
         /// Don't create a profile
         #[arg(short = 'P', long)]
         no_profile: bool,
+        /// Download the loader's libraries now instead of on first launch
+        ///
+        /// This lets the first launch work without access to the Quilt maven.
+        #[arg(short = 'L', long)]
+        download_libraries: bool,
+        /// Don't apply a fixup to the launch profile, see --verbose for the available fixups
+        #[arg(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(fixups::FIXUPS.iter().map(|f| f.name)))]
+        disable_fixup: Vec<String>,
+        /// Also install the newest compatible Quilted Fabric API into the mods folder
+        #[arg(short = 'q', long)]
+        qfapi: bool,
+        /// The base URL of the Modrinth API, used to find Quilted Fabric API
+        #[arg(long, default_value = modrinth::DEFAULT_API_URL)]
+        modrinth_api_url: String,
+        /// Record the exact versions, downloads and options in a lockfile,
+        /// to reproduce the installation with `install --lock`
+        #[arg(long, value_name = "FILE")]
+        write_lock: Option<PathBuf>,
+        /// The directory to install to
+        #[arg(
+            short = 'o',
+            long,
+            default_value_os_t = installer::get_default_client_directory()
+        )]
+        install_dir: PathBuf,
+    },
+    /// Reproduce a client installation recorded with `client --write-lock`
+    ///
+    /// Fails if any download differs from the hash in the lockfile.
+    Install {
+        /// The lockfile to reproduce
+        #[arg(long, value_name = "FILE")]
+        lock: PathBuf,
         /// The directory to install to
         #[arg(
             short = 'o',
@@ -54,16 +157,140 @@ pub enum Subcommands {
         /// Do not download the server jar
         #[arg(short = 'J', long)]
         no_jar: bool,
+        /// Also install the newest compatible Quilted Fabric API into the mods folder
+        #[arg(short = 'q', long)]
+        qfapi: bool,
+        /// The base URL of the Modrinth API, used to find Quilted Fabric API
+        #[arg(long, default_value = modrinth::DEFAULT_API_URL)]
+        modrinth_api_url: String,
         /// The directory to install to
         #[arg(short = 'o', long)]
         install_dir: PathBuf,
     },
+    /// Install a CurseForge modpack on top of the Quilt Loader client
+    ///
+    /// The Minecraft and Quilt Loader versions are read from the modpack's manifest.
+    Curseforge {
+        /// The modpack zip to install
+        pack: PathBuf,
+        /// Don't create a profile
+        #[arg(short = 'P', long)]
+        no_profile: bool,
+        /// The directory to install to
+        #[arg(
+            short = 'o',
+            long,
+            default_value_os_t = installer::get_default_client_directory()
+        )]
+        install_dir: PathBuf,
+        /// The CurseForge API key used to download mods
+        ///
+        /// If neither this nor the API URL is given,
+        /// the mods that need downloading are only listed.
+        #[arg(long)]
+        api_key: Option<String>,
+        /// The base URL of the CurseForge API
+        #[arg(long)]
+        api_url: Option<String>,
+    },
+    /// Install a packwiz pack on top of the Quilt Loader client, or its files into a server directory
+    ///
+    /// The Minecraft and Quilt Loader versions are read from the pack.
+    Packwiz {
+        /// The path or HTTP URL of the pack's pack.toml
+        pack: String,
+        /// Only install the server side files of the pack, not the server itself
+        #[arg(short = 's', long)]
+        server: bool,
+        /// Don't create a profile
+        #[arg(short = 'P', long)]
+        no_profile: bool,
+        /// The directory to install to
+        ///
+        /// Defaults to the Minecraft directory for clients, and is required for servers.
+        #[arg(short = 'o', long)]
+        install_dir: Option<PathBuf>,
+    },
+    /// Check the mods of an installation for compatibility with the selected versions
+    CheckMods {
+        /// The directory containing the mods folder
+        #[arg(
+            short = 'o',
+            long,
+            default_value_os_t = installer::get_default_client_directory()
+        )]
+        install_dir: PathBuf,
+    },
+    /// Verify the Quilt versions and profiles of a client installation
+    Verify {
+        /// Redownload missing or corrupt files and remove dangling profiles
+        #[arg(short = 'r', long)]
+        repair: bool,
+        /// The directory to verify
+        #[arg(
+            short = 'o',
+            long,
+            default_value_os_t = installer::get_default_client_directory()
+        )]
+        install_dir: PathBuf,
+    },
+    /// List the Minecraft or Quilt Loader versions available to install
+    Versions {
+        /// Which versions to list
+        #[arg(value_enum, default_value_t)]
+        kind: VersionKind,
+        /// Only list stable versions
+        #[arg(long, conflicts_with_all = ["snapshots", "betas"])]
+        stable: bool,
+        /// Only list Minecraft snapshots, pre-releases and other unstable versions
+        #[arg(long)]
+        snapshots: bool,
+        /// Only list Minecraft versions of these release types: release, snapshot, pre-release,
+        /// release-candidate, april-fools, old-beta or old-alpha
+        #[arg(long = "type", value_name = "TYPE", value_delimiter = ',', value_parser = str::parse::<ReleaseType>)]
+        types: Vec<ReleaseType>,
+        /// Only list Quilt Loader betas
+        #[arg(long)]
+        betas: bool,
+        /// Only list versions matching a regular expression
+        #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+        matching: Option<Regex>,
+        /// Only list the Quilt Loader versions that can be installed for a Minecraft version
+        #[arg(long, value_name = "MINECRAFT_VERSION")]
+        game: Option<String>,
+        /// List at most this many versions, newest first
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+        /// Also look up which Java version each Minecraft version needs, one request per version
+        #[arg(long)]
+        java: bool,
+        /// How to print the versions
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Pick what to install with interactive prompts in the terminal
+    ///
+    /// Ends by printing the equivalent command line, for installing again without prompts.
+    Wizard,
+    /// Install everything described in a config file
+    ///
+    /// Installations and mods that are already in place are left alone,
+    /// so it is safe to run again after changing the file.
+    Apply {
+        /// The config file describing the client and server installations
+        #[arg(default_value = config::DEFAULT_CONFIG_FILE)]
+        config: PathBuf,
+    },
 }
 #[derive(Clone, PartialEq, Eq, Default, Display)]
 pub enum MCVersionCLI {
     #[default]
     Stable,
     Snapshot,
+    Latest,
+    /// A version series, e.g. `1.20` for `1.20.x`
+    #[display(fmt = "{}.x", _0)]
+    Series(String),
     Custom(String),
 }
 
@@ -72,6 +299,9 @@ pub enum LoaderVersionCLI {
     #[default]
     Stable,
     Beta,
+    Latest,
+    Auto,
+    Requirement(VersionReq),
     Custom(String),
 }
 
@@ -80,7 +310,14 @@ impl From<String> for MCVersionCLI {
         match s.to_lowercase().as_ref() {
             "stable" => Self::Stable,
             "snapshot" => Self::Snapshot,
-            _ => Self::Custom(s),
+            "latest" => Self::Latest,
+            lower => match lower
+                .strip_suffix(".x")
+                .or_else(|| lower.strip_suffix(".*"))
+            {
+                Some(series) => Self::Series(series.into()),
+                None => Self::Custom(s),
+            },
         }
     }
 }
@@ -90,81 +327,652 @@ impl From<String> for LoaderVersionCLI {
         match s.to_lowercase().as_ref() {
             "stable" => Self::Stable,
             "beta" => Self::Beta,
-            _ => Self::Custom(s),
+            "latest" => Self::Latest,
+            "auto" => Self::Auto,
+            // A plain version number would also parse as a `^` requirement
+            _ if Version::parse(&s).is_ok() => Self::Custom(s),
+            _ => match VersionReq::parse(&s) {
+                Ok(requirement) => Self::Requirement(requirement),
+                Err(_) => Self::Custom(s),
+            },
         }
     }
 }
 
-pub async fn cli(client: Client, args: Args) -> Result<()> {
-    let (minecraft_version, loader_version) =
-        get_versions(client.clone(), args.minecraft_version, args.loader_version).await?;
+#[derive(Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum VersionKind {
+    #[default]
+    Minecraft,
+    Loader,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+impl Args {
+    /// Parses the command line, taking options that are not given from the environment.
+    /// The matches tell where each value came from.
+    pub fn parse_with_env() -> (Self, ArgMatches) {
+        let matches = with_env(Self::command()).get_matches();
+        let args = Self::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+        (args, matches)
+    }
+}
 
+/// Lets every option of `command` and its subcommands be set through an environment variable
+fn with_env(command: Command) -> Command {
+    let subcommands = command
+        .get_subcommands()
+        .map(|s| s.get_name().to_owned())
+        .collect::<Vec<_>>();
+    let command = command.mut_args(|arg| match arg.get_id().as_str() {
+        "help" | "version" => arg,
+        _ => {
+            let name = env_name(&arg);
+            arg.env(name)
+        }
+    });
+    subcommands.iter().fold(command, |command, name| {
+        command.mut_subcommand(name, with_env)
+    })
+}
+
+/// The environment variable an option can be set with, named after its long flag,
+/// or its id for positional arguments
+fn env_name(arg: &Arg) -> String {
+    let name = arg.get_long().unwrap_or(arg.get_id().as_str());
+    format!("{ENV_PREFIX}{}", name.replace('-', "_").to_uppercase())
+}
+
+/// Prints the value of every option and whether it came from the command line,
+/// the environment or the defaults, to stderr so it stays out of piped output
+pub fn print_value_sources(matches: &ArgMatches) {
+    eprintln!("Options:");
+    let command = with_env(Args::command());
+    let mut next = Some((&command, matches));
+    while let Some((command, current)) = next {
+        for arg in command.get_arguments() {
+            let id = arg.get_id().as_str();
+            let Some(source) = current.value_source(id) else {
+                continue;
+            };
+            let source = match source {
+                ValueSource::CommandLine => "command line".to_owned(),
+                ValueSource::EnvVariable => env_name(arg),
+                _ => "default".to_owned(),
+            };
+            let value = match current.get_raw(id) {
+                // Keep secrets out of logs
+                Some(_) if id == "api_key" => "<hidden>".to_owned(),
+                Some(values) => values
+                    .map(|v| v.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                None => String::new(),
+            };
+            eprintln!("  {id} = {value} ({source})");
+        }
+        next = current
+            .subcommand()
+            .and_then(|(name, matches)| Some((command.find_subcommand(name)?, matches)));
+    }
+}
+
+pub async fn cli(client: Client, args: Args) -> Result<()> {
     match args.subcommand.unwrap() {
         Subcommands::Client {
             no_profile,
+            download_libraries,
+            disable_fixup,
+            qfapi,
+            modrinth_api_url,
+            write_lock,
             install_dir,
         } => {
+            let (minecraft_version, loader_version) = get_versions(
+                client.clone(),
+                args.minecraft_version,
+                args.loader_version,
+                args.loader_channel.unwrap_or_default(),
+                args.as_of,
+                &args.manifest_url,
+                &install_dir.join("mods"),
+            )
+            .await?;
+            if args.verbose {
+                print_fixups(&minecraft_version, &loader_version, &disable_fixup);
+            }
+            let options = LockedOptions {
+                generate_profile: !no_profile,
+                download_libraries,
+                disabled_fixups: disable_fixup,
+                qfapi,
+            };
+            let mut artifacts = installer::install_client(
+                client.clone(),
+                ClientInstallation {
+                    minecraft_version: minecraft_version.clone(),
+                    loader_version: loader_version.clone(),
+                    install_dir: install_dir.clone(),
+                    generate_profile: options.generate_profile,
+                    download_libraries: options.download_libraries,
+                    disabled_fixups: options.disabled_fixups.clone(),
+                    pinned_artifacts: Vec::new(),
+                },
+            )
+            .await?;
+            if qfapi {
+                artifacts.push(
+                    modrinth::install_qfapi(
+                        client,
+                        &modrinth_api_url,
+                        &minecraft_version,
+                        &install_dir.join("mods"),
+                    )
+                    .await?,
+                );
+            }
+            if let Some(path) = write_lock {
+                Lockfile::new(options, minecraft_version, loader_version, artifacts)
+                    .write(&path)?;
+                println!("Wrote lockfile {}", path.display());
+            }
+            Ok(())
+        }
+        Subcommands::Install { lock, install_dir } => {
+            let lockfile = Lockfile::read(&lock)?;
             installer::install_client(
-                client,
+                client.clone(),
                 ClientInstallation {
-                    minecraft_version,
-                    loader_version,
-                    install_dir,
-                    generate_profile: !no_profile,
+                    minecraft_version: lockfile.minecraft_version.clone(),
+                    loader_version: lockfile.loader_version.clone(),
+                    install_dir: install_dir.clone(),
+                    generate_profile: lockfile.options.generate_profile,
+                    download_libraries: lockfile.options.download_libraries,
+                    disabled_fixups: lockfile.options.disabled_fixups.clone(),
+                    pinned_artifacts: lockfile.artifacts.clone(),
                 },
             )
-            .await
+            .await?;
+            if lockfile.options.qfapi {
+                let qfapi = lockfile
+                    .artifacts(ArtifactKind::Mod)
+                    .next()
+                    .ok_or_else(|| InstallError::NotLocked {
+                        artifact: "Quilted Fabric API".into(),
+                    })?;
+                modrinth::install_locked_qfapi(client, qfapi, &install_dir.join("mods")).await?;
+            }
+            Ok(())
         }
         Subcommands::Server {
             no_launch_script,
             no_jar,
+            qfapi,
+            modrinth_api_url,
             install_dir,
         } => {
+            let (minecraft_version, loader_version) = get_versions(
+                client.clone(),
+                args.minecraft_version,
+                args.loader_version,
+                args.loader_channel.unwrap_or_default(),
+                args.as_of,
+                &args.manifest_url,
+                &install_dir.join("mods"),
+            )
+            .await?;
             installer::install_server(
-                client,
+                client.clone(),
                 ServerInstallation {
-                    minecraft_version,
+                    minecraft_version: minecraft_version.clone(),
                     loader_version,
-                    install_dir,
+                    install_dir: install_dir.clone(),
                     download_jar: !no_jar,
                     generate_script: !no_launch_script,
                 },
             )
-            .await
+            .await?;
+            if qfapi {
+                modrinth::install_qfapi(
+                    client,
+                    &modrinth_api_url,
+                    &minecraft_version,
+                    &install_dir.join("mods"),
+                )
+                .await?;
+            }
+            Ok(())
+        }
+        Subcommands::Curseforge {
+            pack,
+            no_profile,
+            install_dir,
+            api_key,
+            api_url,
+        } => {
+            let resolver = (api_key.is_some() || api_url.is_some()).then(|| CurseForgeApi {
+                base_url: api_url.unwrap_or_else(|| curseforge::DEFAULT_API_URL.into()),
+                api_key,
+            });
+            let report = curseforge::install_modpack(
+                client,
+                ModpackInstallation {
+                    pack,
+                    install_dir,
+                    generate_profile: !no_profile,
+                },
+                resolver.as_ref(),
+            )
+            .await?;
+
+            for file in &report.downloaded {
+                println!("Downloaded {}", file.file_name);
+            }
+            if !report.pending.is_empty() {
+                println!("The following mod files need to be downloaded manually:");
+                for file in &report.pending {
+                    println!("  {file}{}", if file.required { "" } else { " (optional)" });
+                }
+            }
+            Ok(())
+        }
+        Subcommands::Packwiz {
+            pack,
+            server,
+            no_profile,
+            install_dir,
+        } => {
+            let (side, install_dir) = if server {
+                (
+                    Installation::Server,
+                    install_dir.context("An installation directory is required for servers")?,
+                )
+            } else {
+                (
+                    Installation::Client,
+                    install_dir.unwrap_or_else(installer::get_default_client_directory),
+                )
+            };
+            let report = packwiz::install_pack(
+                client,
+                PackInstallation {
+                    pack: PackLocation::parse(&pack)?,
+                    side,
+                    install_dir,
+                    generate_profile: !no_profile,
+                },
+            )
+            .await?;
+
+            println!(
+                "Installed {} files, skipped {} files for the other side",
+                report.installed.len(),
+                report.skipped.len()
+            );
+            if !report.pending.is_empty() {
+                println!("The following mods need to be downloaded manually:");
+                for name in &report.pending {
+                    println!("  {name}");
+                }
+            }
+            Ok(())
+        }
+        Subcommands::CheckMods { install_dir } => {
+            let (minecraft_version, loader_version) = get_versions(
+                client,
+                args.minecraft_version,
+                args.loader_version,
+                args.loader_channel.unwrap_or_default(),
+                args.as_of,
+                &args.manifest_url,
+                &install_dir.join("mods"),
+            )
+            .await?;
+            print!(
+                "{}",
+                mods::check_mods(
+                    &install_dir.join("mods"),
+                    &minecraft_version,
+                    &loader_version
+                )?
+            );
+            Ok(())
+        }
+        Subcommands::Versions {
+            kind,
+            stable,
+            snapshots,
+            types,
+            betas,
+            matching,
+            game,
+            limit,
+            java,
+            format,
+        } => {
+            let matches = |version: &str| matching.as_ref().is_none_or(|r| r.is_match(version));
+            let limit = limit.unwrap_or(usize::MAX);
+            match kind {
+                VersionKind::Minecraft => {
+                    if betas || game.is_some() {
+                        bail!("--betas and --game only apply to Quilt Loader versions");
+                    }
+                    let mut versions = installer::fetch_minecraft_versions(client.clone()).await?;
+                    let manifest = installer::fetch_release_details(
+                        client.clone(),
+                        &args.manifest_url,
+                        &mut versions,
+                    )
+                    .await?;
+                    let mut versions = versions
+                        .into_iter()
+                        .filter(|v| (!stable || v.stable) && (!snapshots || !v.stable))
+                        .filter(|v| types.is_empty() || types.contains(&v.release_type))
+                        .filter(|v| matches(&v.version))
+                        .take(limit)
+                        .collect::<Vec<_>>();
+                    if java {
+                        installer::fetch_java_versions(client, &manifest, &mut versions).await;
+                    }
+                    print_versions(
+                        &versions,
+                        format,
+                        &["VERSION", "TYPE", "RELEASED", "JAVA"],
+                        |v| {
+                            vec![
+                                v.version.clone(),
+                                v.release_type.to_string(),
+                                v.release_time
+                                    .map_or_else(String::new, |t| t.format("%Y-%m-%d").to_string()),
+                                v.java_version.map_or_else(String::new, |j| j.to_string()),
+                            ]
+                        },
+                    )
+                }
+                VersionKind::Loader => {
+                    if snapshots || !types.is_empty() || java {
+                        bail!("--snapshots, --type and --java only apply to Minecraft versions");
+                    }
+                    let versions = match game {
+                        Some(game) => {
+                            let minecraft_versions =
+                                installer::fetch_minecraft_versions(client.clone()).await?;
+                            if !minecraft_versions.iter().any(|v| v.version == game) {
+                                return Err(not_found(
+                                    "Minecraft version",
+                                    &game,
+                                    &minecraft_versions,
+                                ));
+                            }
+                            installer::fetch_compatible_loader_versions(client, &game).await?
+                        }
+                        None => installer::fetch_loader_versions(client).await?,
+                    };
+                    let versions = versions
+                        .into_iter()
+                        .filter(|v| !stable || v.channel() == LoaderChannel::Stable)
+                        .filter(|v| !betas || v.channel() == LoaderChannel::Beta)
+                        .filter(|v| args.loader_channel.is_none_or(|c| v.channel() >= c))
+                        .filter(|v| matches(&v.to_string()))
+                        .take(limit)
+                        .collect::<Vec<_>>();
+                    print_versions(&versions, format, &["VERSION", "CHANNEL"], |v| {
+                        vec![v.to_string(), v.channel().to_string()]
+                    })
+                }
+            }
+        }
+        Subcommands::Wizard => wizard::run(client).await,
+        Subcommands::Apply { config } => apply::run(client, &config).await,
+        Subcommands::Verify {
+            repair,
+            install_dir,
+        } => {
+            let findings = verify::verify_installation(client, &install_dir, repair).await?;
+            for finding in &findings {
+                println!("{finding}");
+            }
+
+            let remaining = findings.iter().filter(|f| !f.repaired).count();
+            if remaining == 0 {
+                println!("No problems remaining");
+                Ok(())
+            } else if repair {
+                bail!("{remaining} problems could not be repaired")
+            } else {
+                bail!("Found {remaining} problems, run with --repair to fix them")
+            }
+        }
+    }
+}
+
+/// Prints versions as JSON, or as a table with the columns returned by `row`
+fn print_versions<T: Serialize>(
+    versions: &[T],
+    format: OutputFormat,
+    headers: &[&str],
+    row: impl Fn(&T) -> Vec<String>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(versions)?),
+        OutputFormat::Table => {
+            let rows = versions.iter().map(row).collect::<Vec<_>>();
+            let widths = headers
+                .iter()
+                .enumerate()
+                .map(|(i, header)| {
+                    rows.iter()
+                        .map(|row| row[i].len())
+                        .max()
+                        .unwrap_or(0)
+                        .max(header.len())
+                })
+                .collect::<Vec<_>>();
+            let print_row = |cells: &[&str]| {
+                let line = cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, &width)| format!("{cell:width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                println!("{}", line.trim_end());
+            };
+            print_row(headers);
+            for row in &rows {
+                print_row(&row.iter().map(String::as_str).collect::<Vec<_>>());
+            }
         }
     }
+    Ok(())
 }
 
-async fn get_versions(
+/// Lists the fixups and whether they apply, to stderr like the other --verbose output
+fn print_fixups(
+    minecraft_version: &MinecraftVersion,
+    loader_version: &LoaderVersion,
+    disabled: &[String],
+) {
+    eprintln!("Launch profile fixups:");
+    for fixup in fixups::FIXUPS {
+        let status = if disabled.iter().any(|d| d == fixup.name) {
+            "disabled"
+        } else if (fixup.applies)(minecraft_version, loader_version) {
+            "applies"
+        } else {
+            "not needed"
+        };
+        eprintln!("  {} ({status}): {}", fixup.name, fixup.description);
+    }
+}
+
+pub async fn get_versions(
     client: Client,
     minecraft_version: MCVersionCLI,
     loader_version: LoaderVersionCLI,
+    loader_channel: LoaderChannel,
+    as_of: Option<DateTime<Utc>>,
+    manifest_url: &str,
+    mods_dir: &Path,
 ) -> Result<(MinecraftVersion, LoaderVersion)> {
-    let minecraft_versions = installer::fetch_minecraft_versions(client.clone()).await?;
-    let loader_versions = installer::fetch_loader_versions(client).await?;
-
-    Ok((
-        match minecraft_version {
-            MCVersionCLI::Stable => minecraft_versions.into_iter().find(|v| v.stable).unwrap(),
-            MCVersionCLI::Snapshot => minecraft_versions.into_iter().find(|v| !v.stable).unwrap(),
-            MCVersionCLI::Custom(input) => minecraft_versions
-                .into_iter()
-                .find(|v| v.version == input)
-                .with_context(|| format!("Could not find Minecraft version {}", input))?,
-        },
-        match loader_version {
-            LoaderVersionCLI::Stable => loader_versions
-                .into_iter()
-                .find(|v| v.version.pre.is_empty())
-                .context("Could not find a stable Quilt Loader version")?,
-            LoaderVersionCLI::Beta => loader_versions
-                .into_iter()
-                .find(|v| !v.version.pre.is_empty())
-                .context("Could not find a beta Quilt Loader version")?,
-            LoaderVersionCLI::Custom(input) => loader_versions
-                .into_iter()
-                .find(|v| v.to_string() == input)
-                .with_context(|| format!("Could not find Quilt Loader version {}", input))?,
+    let mut minecraft_versions = installer::fetch_minecraft_versions(client.clone()).await?;
+    if as_of.is_some() {
+        installer::fetch_release_details(client.clone(), manifest_url, &mut minecraft_versions)
+            .await?;
+    }
+    // Versions missing from Mojang's manifest have no release time and never count as released
+    let released = |v: &MinecraftVersion| {
+        as_of.is_none_or(|as_of| v.release_time.is_some_and(|time| time <= as_of))
+    };
+    let minecraft_version = match minecraft_version {
+        MCVersionCLI::Stable => minecraft_versions
+            .iter()
+            .find(|v| v.stable && released(v))
+            .cloned()
+            .context("Could not find a stable Minecraft version released by then")?,
+        MCVersionCLI::Snapshot => minecraft_versions
+            .iter()
+            .find(|v| !v.stable && released(v))
+            .cloned()
+            .context("Could not find a Minecraft snapshot released by then")?,
+        MCVersionCLI::Latest => minecraft_versions
+            .iter()
+            .find(|v| released(v))
+            .cloned()
+            .context("Could not find a Minecraft version released by then")?,
+        MCVersionCLI::Series(series) => newest_stable(
+            "Minecraft version",
+            &series,
+            &minecraft_versions,
+            |v| {
+                (v.version == series || v.version.starts_with(&format!("{series}."))) && released(v)
+            },
+            |v| v.stable,
+        )?,
+        MCVersionCLI::Custom(input) => match minecraft_versions.iter().find(|v| v.version == input)
+        {
+            Some(version) => version.clone(),
+            None => return Err(not_found("Minecraft version", &input, &minecraft_versions)),
         },
-    ))
+    };
+
+    // Only loaders meta has a profile for can be installed for this Minecraft version
+    let loader_versions =
+        installer::fetch_compatible_loader_versions(client.clone(), &minecraft_version.version)
+            .await?;
+    let newest = |matches: fn(&LoaderVersion, LoaderChannel) -> bool| {
+        let candidates = loader_versions
+            .iter()
+            .filter(|v| matches(v, loader_channel))
+            .cloned()
+            .collect::<Vec<_>>();
+        let client = client.clone();
+        async move {
+            match as_of {
+                Some(as_of) => {
+                    installer::find_loader_version_as_of(client, &candidates, as_of).await
+                }
+                None => Ok(candidates.into_iter().next()),
+            }
+        }
+    };
+    let loader_version = match loader_version {
+        LoaderVersionCLI::Stable => newest(|v, channel| v.channel() >= channel)
+            .await?
+            .with_context(|| {
+                format!("Could not find a Quilt Loader version in the {loader_channel} channel or more stable for this Minecraft version")
+            })?,
+        LoaderVersionCLI::Beta => newest(|v, _| v.channel() == LoaderChannel::Beta)
+            .await?
+            .context("Could not find a beta Quilt Loader version for this Minecraft version")?,
+        LoaderVersionCLI::Latest => newest(|_, _| true)
+            .await?
+            .context("Could not find a Quilt Loader version for this Minecraft version")?,
+        LoaderVersionCLI::Auto => {
+            mods::select_loader_version(mods_dir, loader_versions, loader_channel)?
+        }
+        LoaderVersionCLI::Requirement(requirement) => newest_stable(
+            "Quilt Loader version",
+            &requirement.to_string(),
+            &loader_versions,
+            |v| requirement.matches(&v.version),
+            |v| v.channel() >= loader_channel,
+        )?,
+        LoaderVersionCLI::Custom(input) => {
+            match loader_versions.iter().find(|v| v.to_string() == input) {
+                Some(version) => version.clone(),
+                None if installer::fetch_loader_versions(client)
+                    .await?
+                    .iter()
+                    .any(|v| v.to_string() == input) =>
+                {
+                    return Err(InstallError::UnsupportedVersion {
+                        minecraft_version: minecraft_version.version,
+                        loader_version: input,
+                    }
+                    .into())
+                }
+                None => return Err(not_found("Quilt Loader version", &input, &loader_versions)),
+            }
+        }
+    };
+
+    Ok((minecraft_version, loader_version))
+}
+
+/// Parses `--as-of`, taking a plain date to mean the end of that day in UTC
+fn parse_as_of(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| "expected a date like 2024-03-01 or an RFC 3339 time".to_owned())?;
+    Ok(date.and_hms_opt(23, 59, 59).unwrap().and_utc())
+}
+
+/// The newest stable version matching a query.
+/// If only unstable versions match, the query is ambiguous and they are listed instead.
+fn newest_stable<T: Clone + std::fmt::Display>(
+    what: &str,
+    query: &str,
+    versions: &[T],
+    matches: impl Fn(&T) -> bool,
+    stable: impl Fn(&T) -> bool,
+) -> Result<T> {
+    let matching = versions.iter().filter(|v| matches(v)).collect::<Vec<_>>();
+    if let Some(version) = matching.iter().find(|v| stable(v)) {
+        return Ok((*version).clone());
+    }
+    if matching.is_empty() {
+        return Err(not_found(what, query, versions));
+    }
+    bail!(
+        "{what} {query} only matches unstable versions, pick one of: {}",
+        join(matching.iter().take(MAX_CANDIDATES))
+    )
+}
+
+/// How many candidates are listed when a version query can't be resolved
+const MAX_CANDIDATES: usize = 5;
+
+/// An error suggesting the versions `query` might be a typo of,
+/// or those nearest to the bound of a requirement
+fn not_found<T: std::fmt::Display>(what: &str, query: &str, versions: &[T]) -> anyhow::Error {
+    let names = versions.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    InstallError::MetaNotFound {
+        what: format!("{what} {query}"),
+        suggestions: suggest_versions(query, names.iter().map(String::as_str)),
+    }
+    .into()
+}
+
+fn join(versions: impl Iterator<Item = impl std::fmt::Display>) -> String {
+    versions
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
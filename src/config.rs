@@ -0,0 +1,98 @@
+use std::{fs, path::Path, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::hash::Checksum;
+use crate::installer::LoaderChannel;
+use crate::lock::{ArtifactKind, LockedArtifact};
+
+/// The file `apply` reads by default
+pub const DEFAULT_CONFIG_FILE: &str = "quilt-installer.toml";
+
+/// Installations to provision, as described in a `quilt-installer.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default, rename = "client")]
+    pub clients: Vec<ClientConfig>,
+    /// Only read to reject them, as server installation is not supported yet
+    #[serde(default, rename = "server")]
+    servers: Vec<toml::Value>,
+}
+
+/// A client installation, with versions picked like on the command line
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    #[serde(default = "stable")]
+    pub minecraft_version: String,
+    #[serde(default = "stable")]
+    pub loader_version: String,
+    #[serde(default)]
+    pub loader_channel: LoaderChannel,
+    /// Defaults to the launcher's directory
+    pub install_dir: Option<PathBuf>,
+    #[serde(default = "yes")]
+    pub generate_profile: bool,
+    #[serde(default)]
+    pub download_libraries: bool,
+    #[serde(default)]
+    pub disabled_fixups: Vec<String>,
+    /// Install the newest compatible Quilted Fabric API
+    #[serde(default)]
+    pub qfapi: bool,
+    #[serde(default)]
+    pub mods: Vec<ModConfig>,
+}
+
+/// A mod jar to download into the mods folder
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModConfig {
+    pub url: String,
+    /// Like `sha512:<hex>`
+    pub checksum: Checksum,
+    /// Defaults to the last segment of the URL
+    pub file_name: Option<String>,
+}
+
+fn stable() -> String {
+    "stable".into()
+}
+
+fn yes() -> bool {
+    true
+}
+
+impl Config {
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .with_context(|| format!("{} is not a valid config file", path.display()))?;
+        // Checked before anything is installed, so a config is either applied fully or not at all
+        if !config.servers.is_empty() {
+            bail!(
+                "{} has [[server]] entries, but server installation is not supported yet",
+                path.display()
+            );
+        }
+        Ok(config)
+    }
+}
+
+impl ModConfig {
+    pub fn artifact(&self) -> LockedArtifact {
+        let name = self.file_name.clone().unwrap_or_else(|| {
+            let path = self.url.split(['?', '#']).next().unwrap_or_default();
+            path.rsplit('/').next().unwrap_or_default().to_owned()
+        });
+        LockedArtifact {
+            kind: ArtifactKind::Mod,
+            name,
+            url: self.url.clone(),
+            checksum: self.checksum.clone(),
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    fs::{self, File},
+    future::Future,
+    io::{self, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use reqwest::Client;
+use serde::Deserialize;
+use zip::ZipArchive;
+
+use crate::download;
+use crate::hash::{Checksum, HashFormat};
+use crate::installer::{self, ClientInstallation};
+
+pub const DEFAULT_API_URL: &str = "https://api.curseforge.com";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub minecraft: ManifestMinecraft,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub files: Vec<ManifestFile>,
+    #[serde(default = "default_overrides")]
+    pub overrides: String,
+}
+
+fn default_overrides() -> String {
+    "overrides".into()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestMinecraft {
+    pub version: String,
+    #[serde(default)]
+    pub mod_loaders: Vec<ModLoader>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModLoader {
+    pub id: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, derive_more::Display)]
+#[display(fmt = "project {} file {}", project_id, file_id)]
+pub struct ManifestFile {
+    #[serde(rename = "projectID")]
+    pub project_id: u32,
+    #[serde(rename = "fileID")]
+    pub file_id: u32,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl Manifest {
+    /// The Quilt Loader version named by the manifest's mod loaders,
+    /// preferring the primary one if several are listed
+    pub fn quilt_loader_version(&self) -> Result<&str> {
+        let mut quilt_loaders = self
+            .minecraft
+            .mod_loaders
+            .iter()
+            .filter_map(|l| Some((l.id.strip_prefix("quilt-")?, l.primary)))
+            .collect::<Vec<_>>();
+        quilt_loaders.sort_by_key(|(_, primary)| !primary);
+        quilt_loaders
+            .first()
+            .map(|(version, _)| *version)
+            .context("The modpack does not use Quilt Loader")
+    }
+}
+
+/// A mod file whose download location has been resolved
+#[derive(Debug, Clone)]
+pub struct ResolvedFile {
+    pub file_name: String,
+    pub url: String,
+    pub checksum: Option<Checksum>,
+}
+
+/// Resolves the files listed in a manifest to download locations
+pub trait FileResolver {
+    /// Returns `None` if the file cannot be downloaded automatically,
+    /// e.g. because its author has disabled third party distribution
+    fn resolve(
+        &self,
+        client: &Client,
+        file: &ManifestFile,
+    ) -> impl Future<Output = Result<Option<ResolvedFile>>> + Send;
+}
+
+/// Resolves files using the CurseForge Core API, or anything that imitates it
+#[derive(Debug, Clone)]
+pub struct CurseForgeApi {
+    pub base_url: String,
+    pub api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiFile {
+    file_name: String,
+    download_url: Option<String>,
+    #[serde(default)]
+    hashes: Vec<ApiHash>,
+}
+
+#[derive(Deserialize)]
+struct ApiHash {
+    value: String,
+    algo: u8,
+}
+
+impl ApiFile {
+    /// The strongest hash CurseForge knows for the file
+    fn checksum(&self) -> Option<Checksum> {
+        let hash = |algo| self.hashes.iter().find(|h| h.algo == algo);
+        // The API identifies SHA-1 as 1 and MD5 as 2
+        hash(1)
+            .map(|h| Checksum::new(HashFormat::Sha1, &h.value))
+            .or_else(|| hash(2).map(|h| Checksum::new(HashFormat::Md5, &h.value)))
+    }
+}
+
+impl FileResolver for CurseForgeApi {
+    async fn resolve(&self, client: &Client, file: &ManifestFile) -> Result<Option<ResolvedFile>> {
+        let mut request = client.get(format!(
+            "{}/v1/mods/{}/files/{}",
+            self.base_url.trim_end_matches('/'),
+            file.project_id,
+            file.file_id
+        ));
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        let response: ApiResponse<ApiFile> =
+            request.send().await?.error_for_status()?.json().await?;
+
+        let checksum = response.data.checksum();
+        Ok(response.data.download_url.map(|url| ResolvedFile {
+            file_name: response.data.file_name,
+            url,
+            checksum,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModpackInstallation {
+    pub pack: PathBuf,
+    pub install_dir: PathBuf,
+    pub generate_profile: bool,
+}
+
+/// The mod files of a modpack, split by whether they were downloaded
+#[derive(Debug, Default)]
+pub struct ModpackReport {
+    pub downloaded: Vec<ResolvedFile>,
+    pub pending: Vec<ManifestFile>,
+}
+
+/// Installs a CurseForge modpack zip on top of a Quilt client installation.
+///
+/// Without a resolver, every mod file is reported as pending.
+pub async fn install_modpack(
+    client: Client,
+    args: ModpackInstallation,
+    resolver: Option<&impl FileResolver>,
+) -> Result<ModpackReport> {
+    let mut archive = ZipArchive::new(File::open(&args.pack)?)
+        .with_context(|| format!("{} is not a valid modpack", args.pack.display()))?;
+    let manifest = read_manifest(&mut archive)?;
+    info!(
+        "Installing modpack {} for Minecraft {}",
+        manifest.name, manifest.minecraft.version
+    );
+
+    let (minecraft_version, loader_version) = installer::find_versions(
+        client.clone(),
+        &manifest.minecraft.version,
+        manifest.quilt_loader_version()?,
+    )
+    .await?;
+
+    installer::install_client(
+        client.clone(),
+        ClientInstallation {
+            minecraft_version,
+            loader_version,
+            install_dir: args.install_dir.clone(),
+            generate_profile: args.generate_profile,
+            download_libraries: false,
+            disabled_fixups: Vec::new(),
+            pinned_artifacts: Vec::new(),
+        },
+    )
+    .await?;
+
+    extract_overrides(&mut archive, &manifest.overrides, &args.install_dir)?;
+
+    let Some(resolver) = resolver else {
+        return Ok(ModpackReport {
+            downloaded: Vec::new(),
+            pending: manifest.files,
+        });
+    };
+    let (resolved, pending) = resolve_files(&client, manifest.files, resolver).await?;
+    download_files(&client, &resolved, &args.install_dir.join("mods")).await?;
+    Ok(ModpackReport {
+        downloaded: resolved,
+        pending,
+    })
+}
+
+fn read_manifest<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Manifest> {
+    let manifest = archive
+        .by_name("manifest.json")
+        .context("The modpack does not contain a manifest.json")?;
+    Ok(serde_json::from_reader(manifest)?)
+}
+
+/// Splits the files into those the resolver found a download for and those it did not
+async fn resolve_files(
+    client: &Client,
+    files: Vec<ManifestFile>,
+    resolver: &impl FileResolver,
+) -> Result<(Vec<ResolvedFile>, Vec<ManifestFile>)> {
+    let mut resolved = Vec::new();
+    let mut pending = Vec::new();
+    for file in files {
+        match resolver.resolve(client, &file).await? {
+            Some(file) => resolved.push(file),
+            None => pending.push(file),
+        }
+    }
+    Ok((resolved, pending))
+}
+
+async fn download_files(client: &Client, files: &[ResolvedFile], mods_dir: &Path) -> Result<()> {
+    fs::create_dir_all(mods_dir)?;
+    for resolved in files {
+        // Only keep the file name so the server cannot write outside the mods folder
+        let Some(file_name) = Path::new(&resolved.file_name).file_name() else {
+            bail!("Invalid file name {}", resolved.file_name);
+        };
+        let bytes = match &resolved.checksum {
+            Some(checksum) => {
+                download::download_verified(client, &resolved.file_name, &resolved.url, checksum)
+                    .await?
+            }
+            None => client
+                .get(&resolved.url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec(),
+        };
+        download::place(&mods_dir.join(file_name), &bytes)?;
+    }
+    Ok(())
+}
+
+fn extract_overrides<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    overrides: &str,
+    install_dir: &Path,
+) -> Result<()> {
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(path) = entry.enclosed_name() else {
+            continue;
+        };
+        let Ok(relative) = path.strip_prefix(overrides) else {
+            continue;
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let target = install_dir.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut entry, &mut File::create(&target)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    const MANIFEST: &str = r#"{
+        "minecraft": {
+            "version": "1.20.1",
+            "modLoaders": [
+                { "id": "forge-47.2.0" },
+                { "id": "quilt-0.21.0", "primary": false },
+                { "id": "quilt-0.21.2", "primary": true }
+            ]
+        },
+        "name": "Test Pack",
+        "files": [
+            { "projectID": 1, "fileID": 10 },
+            { "projectID": 2, "fileID": 20, "required": false }
+        ],
+        "overrides": "extra"
+    }"#;
+
+    fn modpack() -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        for (name, contents) in [
+            ("manifest.json", MANIFEST),
+            ("extra/config/test.toml", "enabled = true"),
+            ("extra/options.txt", "fov:90"),
+            ("overrides/ignored.txt", "not the overrides folder"),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        ZipArchive::new(zip.finish().unwrap()).unwrap()
+    }
+
+    /// Resolves the files of project 1 only
+    struct StubResolver;
+
+    impl FileResolver for StubResolver {
+        async fn resolve(
+            &self,
+            _client: &Client,
+            file: &ManifestFile,
+        ) -> Result<Option<ResolvedFile>> {
+            Ok((file.project_id == 1).then(|| ResolvedFile {
+                file_name: format!("mod-{}.jar", file.file_id),
+                url: format!("https://example.com/{}", file.file_id),
+                checksum: None,
+            }))
+        }
+    }
+
+    #[test]
+    fn reads_the_manifest() {
+        let manifest = read_manifest(&mut modpack()).unwrap();
+
+        assert_eq!(manifest.name, "Test Pack");
+        assert_eq!(manifest.minecraft.version, "1.20.1");
+        assert_eq!(manifest.quilt_loader_version().unwrap(), "0.21.2");
+        assert_eq!(manifest.files.len(), 2);
+        assert!(!manifest.files[1].required);
+    }
+
+    #[test]
+    fn requires_a_quilt_loader() {
+        let mut manifest = read_manifest(&mut modpack()).unwrap();
+        manifest
+            .minecraft
+            .mod_loaders
+            .retain(|l| l.id.starts_with("forge-"));
+
+        assert!(manifest.quilt_loader_version().is_err());
+    }
+
+    #[test]
+    fn extracts_only_the_overrides() {
+        let install_dir = TempDir::new("curseforge-overrides");
+        let mut archive = modpack();
+        let manifest = read_manifest(&mut archive).unwrap();
+
+        extract_overrides(&mut archive, &manifest.overrides, &install_dir).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(install_dir.join("config/test.toml")).unwrap(),
+            "enabled = true"
+        );
+        assert_eq!(
+            fs::read_to_string(install_dir.join("options.txt")).unwrap(),
+            "fov:90"
+        );
+        assert!(!install_dir.join("ignored.txt").exists());
+        assert!(!install_dir.join("manifest.json").exists());
+    }
+
+    #[test]
+    fn splits_resolved_and_pending_files() {
+        let manifest = read_manifest(&mut modpack()).unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let (resolved, pending) = runtime
+            .block_on(resolve_files(&Client::new(), manifest.files, &StubResolver))
+            .unwrap();
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].file_name, "mod-10.jar");
+        assert_eq!(resolved[0].url, "https://example.com/10");
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].project_id, 2);
+    }
+}
@@ -0,0 +1,89 @@
+use std::{ffi::OsString, fs, path::Path};
+
+use log::warn;
+use reqwest::{Client, StatusCode};
+
+use crate::error::InstallError;
+use crate::hash::{Checksum, HashFormat};
+
+/// Companion checksum files to look for next to maven artifacts, strongest first
+const MAVEN_HASH_FORMATS: &[HashFormat] =
+    &[HashFormat::Sha512, HashFormat::Sha256, HashFormat::Sha1];
+
+async fn download(client: &Client, url: &str) -> Result<Vec<u8>, InstallError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(InstallError::network(url))?;
+    Ok(response
+        .bytes()
+        .await
+        .map_err(InstallError::network(url))?
+        .to_vec())
+}
+
+/// Downloads `url`, verifying it against `checksum`.
+/// A mismatch is retried once, in case the first download was corrupted in transit.
+pub async fn download_verified(
+    client: &Client,
+    artifact: &str,
+    url: &str,
+    checksum: &Checksum,
+) -> Result<Vec<u8>, InstallError> {
+    let bytes = download(client, url).await?;
+    if checksum.verify(artifact, &bytes).is_ok() {
+        return Ok(bytes);
+    }
+
+    warn!("Checksum mismatch for {artifact}, downloading again");
+    let bytes = download(client, url).await?;
+    checksum.verify(artifact, &bytes)?;
+    Ok(bytes)
+}
+
+/// Fetches the strongest checksum a maven repository publishes for the artifact at `url`
+pub async fn fetch_maven_checksum(
+    client: &Client,
+    artifact: &str,
+    url: &str,
+) -> Result<Checksum, InstallError> {
+    for &format in MAVEN_HASH_FORMATS {
+        let checksum_url = format!("{url}.{}", format.extension());
+        let response = client
+            .get(&checksum_url)
+            .send()
+            .await
+            .map_err(InstallError::network(&checksum_url))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            continue;
+        }
+        let text = match response.error_for_status() {
+            Ok(response) => response.text().await,
+            Err(error) => Err(error),
+        }
+        .map_err(InstallError::network(&checksum_url))?;
+        // Checksum files may be followed by the file name
+        if let Some(value) = text.split_whitespace().next() {
+            return Ok(Checksum::new(format, value));
+        }
+    }
+    Err(InstallError::MetaNotFound {
+        what: format!("a checksum for {artifact}"),
+        suggestions: Vec::new(),
+    })
+}
+
+/// Writes verified contents to `path` through a temporary file,
+/// so an interrupted write never leaves a corrupt file in place
+pub fn place(path: &Path, contents: &[u8]) -> Result<(), InstallError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(InstallError::io(parent))?;
+    }
+    let mut temporary = OsString::from(path.as_os_str());
+    temporary.push(".part");
+    fs::write(&temporary, contents).map_err(InstallError::io(&temporary))?;
+    fs::rename(&temporary, path).map_err(InstallError::io(path))?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::{io, path::PathBuf};
+
+use derive_more::{Display, Error};
+
+use crate::hash::HashMismatch;
+
+/// Why an installation failed, so frontends can react to each kind of failure
+#[derive(Debug, Display, Error)]
+pub enum InstallError {
+    #[display(fmt = "Could not download {}", url)]
+    Network { url: String, source: reqwest::Error },
+    #[display(fmt = "Could not find {}{}", what, "did_you_mean(suggestions)")]
+    MetaNotFound {
+        what: String,
+        /// Similar names that do exist
+        suggestions: Vec<String>,
+    },
+    #[display(fmt = "{} is not a valid installation directory", "path.display()")]
+    InvalidInstallDir { path: PathBuf },
+    #[display(fmt = "The profile from {} is corrupt: {}", origin, reason)]
+    CorruptProfile { origin: String, reason: String },
+    #[display(fmt = "Could not access {}", "path.display()")]
+    Io { path: PathBuf, source: io::Error },
+    #[display(fmt = "{}", _0)]
+    HashMismatch(#[error(not(source))] HashMismatch),
+    #[display(
+        fmt = "Quilt Loader {} is not available for Minecraft {}",
+        loader_version,
+        minecraft_version
+    )]
+    UnsupportedVersion {
+        minecraft_version: String,
+        loader_version: String,
+    },
+    /// A kind of installation this version of the installer can't do yet
+    #[display(fmt = "{} is not supported yet", what)]
+    Unsupported { what: String },
+    #[display(fmt = "{} is not a valid lockfile: {}", "path.display()", reason)]
+    InvalidLockfile { path: PathBuf, reason: String },
+    /// An artifact to install that the lockfile does not pin, so it was made for another installation
+    #[display(fmt = "{} is not in the lockfile", artifact)]
+    NotLocked { artifact: String },
+}
+
+impl InstallError {
+    pub fn network(url: impl Into<String>) -> impl FnOnce(reqwest::Error) -> Self {
+        let url = url.into();
+        move |source| Self::Network { url, source }
+    }
+
+    pub fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+
+    /// The exit code of the CLI when failing with this error.
+    /// 1 is left for other failures and 2 for invalid arguments.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Network { .. } => 3,
+            Self::MetaNotFound { .. } => 4,
+            Self::InvalidInstallDir { .. } => 5,
+            Self::CorruptProfile { .. } => 6,
+            Self::Io { .. } => 7,
+            Self::HashMismatch(_) => 8,
+            Self::UnsupportedVersion { .. } => 9,
+            Self::Unsupported { .. } => 10,
+            Self::InvalidLockfile { .. } => 11,
+            Self::NotLocked { .. } => 12,
+        }
+    }
+
+    /// What the user can do about the error
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::Network { .. } => "Check your internet connection and try again.",
+            Self::MetaNotFound { .. } => {
+                "Check that the versions exist, or pick one from the list of available versions."
+            }
+            Self::InvalidInstallDir { .. } => {
+                "Select the .minecraft directory, and start the launcher once if it has never been opened."
+            }
+            Self::CorruptProfile { .. } => {
+                "Open the Minecraft launcher once so it rewrites its files, then try again."
+            }
+            Self::Io { .. } => {
+                "Check that the directory is writable, the disk is not full and the launcher is closed."
+            }
+            Self::HashMismatch(_) => {
+                "The download was corrupted. Try again later, or from another network."
+            }
+            Self::UnsupportedVersion { .. } => {
+                "Pick an older Quilt Loader version, or another Minecraft version."
+            }
+            Self::Unsupported { .. } => "Use the official Quilt installer for this for now.",
+            Self::InvalidLockfile { .. } => {
+                "Write the lockfile again with `client --write-lock`, using the latest installer."
+            }
+            Self::NotLocked { .. } => {
+                "The lockfile does not match the installation, write it again with `client --write-lock`."
+            }
+        }
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(", did you mean {}?", suggestions.join(" or "))
+    }
+}
+
+impl From<HashMismatch> for InstallError {
+    fn from(mismatch: HashMismatch) -> Self {
+        Self::HashMismatch(mismatch)
+    }
+}
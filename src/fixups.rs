@@ -0,0 +1,126 @@
+use semver::Version;
+
+use crate::installer::{LoaderVersion, MinecraftVersion};
+use crate::profile::LaunchProfile;
+
+/// A workaround for a problem in the launch profiles meta serves for some versions
+pub struct Fixup {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether the fixup is needed for a Minecraft and Quilt Loader version
+    pub applies: fn(&MinecraftVersion, &LoaderVersion) -> bool,
+    pub apply: fn(&mut LaunchProfile),
+}
+
+pub const FIXUPS: &[Fixup] = &[Fixup {
+    name: "remove-hashed",
+    description: "Remove hashed mappings, which make loaders below 0.17.7 silently fail remapping \
+                  when provided alongside intermediary",
+    applies: |_, loader| loader.version < Version::new(0, 17, 7),
+    apply: |profile| {
+        profile
+            .libraries
+            .retain(|lib| !lib.name.starts_with("org.quiltmc:hashed"))
+    },
+}];
+
+/// Applies the fixups needed for the versions that are not in `disabled`,
+/// returning the names of those applied
+pub fn apply_fixups(
+    profile: &mut LaunchProfile,
+    minecraft_version: &MinecraftVersion,
+    loader_version: &LoaderVersion,
+    disabled: &[String],
+) -> Vec<&'static str> {
+    FIXUPS
+        .iter()
+        .filter(|f| (f.applies)(minecraft_version, loader_version))
+        .filter(|f| !disabled.iter().any(|d| d == f.name))
+        .map(|f| {
+            (f.apply)(profile);
+            f.name
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> LaunchProfile {
+        let json = match name {
+            "0.17.6" => include_str!("../tests/fixtures/profile-0.17.6-1.19.2.json"),
+            "0.17.7" => include_str!("../tests/fixtures/profile-0.17.7-1.19.2.json"),
+            _ => unreachable!(),
+        };
+        LaunchProfile::parse(json, name).unwrap()
+    }
+
+    fn library_names(profile: &LaunchProfile) -> Vec<&str> {
+        profile.libraries.iter().map(|l| l.name.as_str()).collect()
+    }
+
+    #[test]
+    fn fixup_names_are_unique() {
+        for (i, fixup) in FIXUPS.iter().enumerate() {
+            assert!(FIXUPS[i + 1..].iter().all(|f| f.name != fixup.name));
+        }
+    }
+
+    #[test]
+    fn remove_hashed_applies_below_0_17_7() {
+        let minecraft = MinecraftVersion::from_id("1.19.2");
+        let loader = LoaderVersion::from_version(Version::new(0, 17, 6));
+        let mut profile = fixture("0.17.6");
+
+        let applied = apply_fixups(&mut profile, &minecraft, &loader, &[]);
+
+        assert_eq!(applied, ["remove-hashed"]);
+        assert_eq!(
+            library_names(&profile),
+            [
+                "net.fabricmc:intermediary:1.19.2",
+                "org.quiltmc:quilt-loader:0.17.6",
+                "net.fabricmc:sponge-mixin:0.11.4+mixin.0.8.5",
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_hashed_skips_fixed_loaders() {
+        let minecraft = MinecraftVersion::from_id("1.19.2");
+        let loader = LoaderVersion::from_version(Version::new(0, 17, 7));
+        let mut profile = fixture("0.17.7");
+
+        let applied = apply_fixups(&mut profile, &minecraft, &loader, &[]);
+
+        assert!(applied.is_empty());
+        assert!(library_names(&profile).contains(&"org.quiltmc:hashed:1.19.2"));
+    }
+
+    #[test]
+    fn remove_hashed_can_be_disabled() {
+        let minecraft = MinecraftVersion::from_id("1.19.2");
+        let loader = LoaderVersion::from_version(Version::new(0, 17, 6));
+        let mut profile = fixture("0.17.6");
+
+        let applied = apply_fixups(&mut profile, &minecraft, &loader, &["remove-hashed".into()]);
+
+        assert!(applied.is_empty());
+        assert_eq!(profile.libraries.len(), 4);
+    }
+
+    #[test]
+    fn remove_hashed_keeps_other_fields() {
+        let minecraft = MinecraftVersion::from_id("1.19.2");
+        let loader = LoaderVersion::from_version(Version::new(0, 17, 6));
+        let mut profile = fixture("0.17.6");
+
+        apply_fixups(&mut profile, &minecraft, &loader, &[]);
+        let profile = LaunchProfile::parse(&profile.to_json(), "round trip").unwrap();
+
+        assert_eq!(profile.inherits_from.as_deref(), Some("1.19.2"));
+        assert_eq!(profile.other["type"], "release");
+        assert_eq!(profile.other["releaseTime"], "2022-11-01T17:23:10+0000");
+    }
+}
@@ -1,10 +1,7 @@
-use std::borrow::Cow;
-use std::fmt::Debug;
-use std::path::PathBuf;
-
-use anyhow::{anyhow, Error, Result};
+use anyhow::{Error, Result};
 use iced::widget::{
-    Button, Checkbox, Column, PickList, ProgressBar, Radio, Row, Rule, Space, Text, TextInput,
+    tooltip, Button, Checkbox, Column, PickList, ProgressBar, Radio, Row, Rule, Scrollable, Space,
+    Text, TextInput, Tooltip,
 };
 use iced::Size;
 use iced::{
@@ -13,30 +10,73 @@ use iced::{
 use png::Transformations;
 use reqwest::Client;
 use rfd::{FileDialog, MessageDialog, MessageLevel};
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::installer::{
-    fetch_loader_versions, fetch_minecraft_versions, get_default_client_directory, install_client,
-    install_server, ClientInstallation, Installation, LoaderVersion, MinecraftVersion,
-    ServerInstallation,
+use quilt_installer::installer::{
+    fetch_compatible_loader_versions, fetch_java_versions, fetch_minecraft_versions,
+    fetch_release_details, Installation, LoaderChannel, LoaderVersion, MinecraftVersion,
 };
+use quilt_installer::mods::{check_mods, select_loader_version, CompatibilityReport};
+use quilt_installer::mojang::{ReleaseType, VersionManifest};
+use quilt_installer::InstallError;
+
+use crate::choices::Choices;
 
-pub fn run(client: Client) -> Result<()> {
-    State::run(Settings {
+/// Opens the installer window, reading release details from the version manifest at `manifest_url`
+pub fn run(client: Client, manifest_url: String) -> Result<()> {
+    let settings = Settings {
         window: window::Settings {
-            size: Size::new(600., 300.),
+            size: Size::new(600., 340.),
             resizable: false,
             icon: Some(create_icon()?),
             ..Default::default()
         },
-        flags: client,
+        flags: (client, manifest_url),
         ..Default::default()
+    };
+
+    // winit panics instead of returning an error when it can't connect to the display.
+    // That happens while creating the event loop, before the application is created,
+    // so later panics are bugs and are passed on.
+    panic::catch_unwind(AssertUnwindSafe(|| State::run(settings))).unwrap_or_else(|panic| {
+        if STARTED.load(Ordering::Relaxed) {
+            panic::resume_unwind(panic);
+        }
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(iced::Error::WindowCreationFailed(message.into()))
     })?;
 
     Ok(())
 }
 
+/// Set once the event loop exists and the application is created
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether a display to open the window on might be available
+pub fn display_available() -> bool {
+    !cfg!(target_os = "linux")
+        || ["DISPLAY", "WAYLAND_DISPLAY"]
+            .into_iter()
+            .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
+}
+
+/// Whether `run` failed because the window could not be opened
+pub fn is_window_error(error: &Error) -> bool {
+    matches!(
+        error.downcast_ref::<iced::Error>(),
+        Some(iced::Error::WindowCreationFailed(_) | iced::Error::GraphicsCreationFailed(_))
+    )
+}
+
 fn create_icon() -> Result<window::Icon> {
-    let mut decoder = png::Decoder::new(crate::ICON);
+    let mut decoder = png::Decoder::new(quilt_installer::ICON);
     decoder.set_transformations(Transformations::EXPAND);
     let mut reader = decoder.read_info()?;
     let mut buffer = vec![0; reader.output_buffer_size()];
@@ -51,26 +91,10 @@ fn create_icon() -> Result<window::Icon> {
 
 #[derive(Debug, Default)]
 struct State {
-    // Minecraft version picker
-    minecraft_versions: Vec<MinecraftVersion>,
-    selected_minecraft_version: Option<MinecraftVersion>,
-    show_snapshots: bool,
-
-    // Quilt Loader version picker
-    loader_versions: Vec<LoaderVersion>,
-    selected_loader_version: Option<LoaderVersion>,
-    show_betas: bool,
-
-    installation_type: Installation,
-
-    // Client settings
-    client_location: PathBuf,
-    generate_profile: bool,
-
-    // Server settings
-    server_location: PathBuf,
-    download_server_jar: bool,
-    generate_launch_script: bool,
+    choices: Choices,
+    mods_report: Option<String>,
+    /// For looking up the Java version of the selected Minecraft version
+    manifest: Option<VersionManifest>,
 
     // Progress information
     is_installing: bool,
@@ -86,9 +110,16 @@ enum Message {
     Install,
     BrowseClientLocation,
     BrowseServerLocation,
-    SetMcVersions(Result<Vec<MinecraftVersion>>),
-    SetLoaderVersions(Result<Vec<LoaderVersion>>),
+    SetMcVersions(Result<(Vec<MinecraftVersion>, Option<VersionManifest>)>),
+    /// The Java version a Minecraft version needs
+    SetJavaVersion(String, Option<u32>),
+    /// The loader versions compatible with a Minecraft version
+    SetLoaderVersions(String, Result<Vec<LoaderVersion>>),
     DoneInstalling(Result<()>),
+    CheckMods,
+    SetModsReport(Result<CompatibilityReport>),
+    AutoLoaderVersion,
+    SetAutoLoaderVersion(Result<LoaderVersion>),
     Error(Error),
 }
 
@@ -101,12 +132,91 @@ enum Interaction {
     SelectLoaderVersion(LoaderVersion),
     SelectMcVersion(MinecraftVersion),
     SetShowSnapshots(bool),
-    SetShowBetas(bool),
+    SetLoaderChannel(LoaderChannel),
+    AutoLoaderVersion,
     GenerateLaunchScript(bool),
     GenerateProfile(bool),
+    DownloadLibraries(bool),
     ChangeServerLocation(String),
     BrowseServerLocation,
     DownloadServerJar(bool),
+    InstallQfapi(bool),
+    CheckMods,
+    CloseModsReport,
+}
+
+impl State {
+    /// Fetches the loader versions that can be installed for the selected Minecraft version
+    fn fetch_loader_versions(&self) -> Command<Message> {
+        let Some(minecraft_version) = self.choices.selected_minecraft_version.clone() else {
+            return Command::none();
+        };
+        let client = self.client.clone();
+        Command::perform(
+            async move {
+                let versions =
+                    fetch_compatible_loader_versions(client, &minecraft_version.version).await;
+                (minecraft_version.version, versions)
+            },
+            |(minecraft_version, versions)| {
+                Message::SetLoaderVersions(minecraft_version, versions.map_err(Error::from))
+            },
+        )
+    }
+
+    /// Looks up the Java version of the selected Minecraft version, unless already known
+    fn fetch_java_version(&self) -> Command<Message> {
+        let (Some(manifest), Some(minecraft_version)) =
+            (&self.manifest, &self.choices.selected_minecraft_version)
+        else {
+            return Command::none();
+        };
+        if minecraft_version.java_version.is_some() {
+            return Command::none();
+        }
+        let client = self.client.clone();
+        let manifest = manifest.clone();
+        let mut versions = [minecraft_version.clone()];
+        Command::perform(
+            async move {
+                fetch_java_versions(client, &manifest, &mut versions).await;
+                let [version] = versions;
+                (version.version, version.java_version)
+            },
+            |(minecraft_version, java_version)| {
+                Message::SetJavaVersion(minecraft_version, java_version)
+            },
+        )
+    }
+}
+
+/// The Minecraft versions, with release details when Mojang's version manifest is reachable
+async fn fetch_minecraft_versions_with_details(
+    client: Client,
+    manifest_url: String,
+) -> Result<(Vec<MinecraftVersion>, Option<VersionManifest>)> {
+    let mut versions = fetch_minecraft_versions(client.clone()).await?;
+    let manifest = match fetch_release_details(client, &manifest_url, &mut versions).await {
+        Ok(manifest) => Some(manifest),
+        Err(error) => {
+            eprintln!("Could not fetch release details: {error:#}");
+            None
+        }
+    };
+    Ok((versions, manifest))
+}
+
+/// A Minecraft version in the picker, labelled with its release type unless a release
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MinecraftVersionLabel(MinecraftVersion);
+
+impl std::fmt::Display for MinecraftVersionLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.release_type {
+            ReleaseType::Release => write!(f, "{}", self.0),
+            release_type => write!(f, "{} ({release_type})", self.0),
+        }
+    }
 }
 
 impl From<Message> for Command<Message> {
@@ -118,7 +228,7 @@ impl From<Message> for Command<Message> {
 impl Application for State {
     type Message = Message;
     type Executor = executor::Default;
-    type Flags = Client;
+    type Flags = (Client, String);
     type Theme = Theme;
 
     fn theme(&self) -> Self::Theme {
@@ -130,24 +240,17 @@ impl Application for State {
         Theme::Dracula
     }
 
-    fn new(client: Client) -> (Self, Command<Self::Message>) {
+    fn new((client, manifest_url): Self::Flags) -> (Self, Command<Self::Message>) {
+        STARTED.store(true, Ordering::Relaxed);
         (
             State {
-                client_location: get_default_client_directory(),
-                generate_profile: true,
-                server_location: std::env::current_dir().unwrap_or_default(),
-                download_server_jar: true,
-                generate_launch_script: true,
                 client: client.clone(),
                 ..Default::default()
             },
-            Command::batch([
-                Command::perform(
-                    fetch_minecraft_versions(client.clone()),
-                    Message::SetMcVersions,
-                ),
-                Command::perform(fetch_loader_versions(client), Message::SetLoaderVersions),
-            ]),
+            Command::perform(
+                fetch_minecraft_versions_with_details(client, manifest_url),
+                Message::SetMcVersions,
+            ),
         )
     }
 
@@ -159,149 +262,112 @@ impl Application for State {
         match message {
             Message::Interaction(interaction) => match interaction {
                 Interaction::ChangeClientLocation(location) => {
-                    self.client_location = location.into();
+                    self.choices.client_location = location.into();
                 }
                 Interaction::BrowseClientLocation => return Message::BrowseClientLocation.into(),
                 Interaction::Install => return Message::Install.into(),
-                Interaction::SelectInstallation(i) => self.installation_type = i,
-                Interaction::SelectLoaderVersion(v) => self.selected_loader_version = Some(v),
-                Interaction::SelectMcVersion(v) => self.selected_minecraft_version = Some(v),
+                Interaction::SelectInstallation(i) => self.choices.installation_type = i,
+                Interaction::SelectLoaderVersion(v) => {
+                    self.choices.selected_loader_version = Some(v)
+                }
+                Interaction::SelectMcVersion(v) => {
+                    self.choices.selected_minecraft_version = Some(v);
+                    return Command::batch([
+                        self.fetch_loader_versions(),
+                        self.fetch_java_version(),
+                    ]);
+                }
                 Interaction::SetShowSnapshots(enable) => {
-                    self.show_snapshots = enable;
-                    self.selected_minecraft_version = self
-                        .minecraft_versions
-                        .iter()
-                        .find(|v| enable || v.stable)
-                        .cloned();
+                    self.choices.set_show_snapshots(enable);
+                    return Command::batch([
+                        self.fetch_loader_versions(),
+                        self.fetch_java_version(),
+                    ]);
                 }
-                Interaction::SetShowBetas(enable) => {
-                    self.show_betas = enable;
-                    self.selected_loader_version = self
-                        .loader_versions
-                        .iter()
-                        .find(|v| enable || v.version.pre.is_empty())
-                        .cloned();
+                Interaction::SetLoaderChannel(channel) => self.choices.set_loader_channel(channel),
+                Interaction::AutoLoaderVersion => return Message::AutoLoaderVersion.into(),
+                Interaction::GenerateLaunchScript(value) => {
+                    self.choices.generate_launch_script = value
                 }
-                Interaction::GenerateLaunchScript(value) => self.generate_launch_script = value,
-                Interaction::GenerateProfile(value) => self.generate_profile = value,
+                Interaction::GenerateProfile(value) => self.choices.generate_profile = value,
+                Interaction::DownloadLibraries(value) => self.choices.download_libraries = value,
                 Interaction::ChangeServerLocation(location) => {
-                    self.server_location = location.into();
+                    self.choices.server_location = location.into();
                 }
                 Interaction::BrowseServerLocation => return Message::BrowseServerLocation.into(),
-                Interaction::DownloadServerJar(value) => self.download_server_jar = value,
+                Interaction::DownloadServerJar(value) => self.choices.download_server_jar = value,
+                Interaction::InstallQfapi(value) => self.choices.install_qfapi = value,
+                Interaction::CheckMods => return Message::CheckMods.into(),
+                Interaction::CloseModsReport => self.mods_report = None,
             },
-            Message::SetMcVersions(result) => {
-                match result {
-                    Ok(versions) => self.minecraft_versions = versions,
-                    Err(error) => return Message::Error(error).into(),
-                }
-                if self.selected_minecraft_version.is_none() {
-                    self.selected_minecraft_version = self
-                        .minecraft_versions
-                        .iter()
-                        .find(|v| self.show_snapshots || v.stable)
-                        .cloned();
-                }
-            }
-            Message::SetLoaderVersions(result) => {
-                match result {
-                    Ok(versions) => self.loader_versions = versions,
-                    Err(error) => return Message::Error(error).into(),
+            Message::SetMcVersions(result) => match result {
+                Ok((versions, manifest)) => {
+                    self.choices.set_minecraft_versions(versions);
+                    self.manifest = manifest;
+                    return Command::batch([
+                        self.fetch_loader_versions(),
+                        self.fetch_java_version(),
+                    ]);
                 }
-                if self.selected_loader_version.is_none() {
-                    self.selected_loader_version = self
-                        .loader_versions
-                        .iter()
-                        .find(|v| self.show_betas || v.version.pre.is_empty())
-                        .cloned();
+                Err(error) => return Message::Error(error).into(),
+            },
+            Message::SetJavaVersion(minecraft_version, java_version) => {
+                let known = self
+                    .choices
+                    .minecraft_versions
+                    .iter_mut()
+                    .chain(&mut self.choices.selected_minecraft_version)
+                    .filter(|v| v.version == minecraft_version);
+                for version in known {
+                    version.java_version = java_version;
                 }
             }
+            Message::SetLoaderVersions(minecraft_version, result) => match result {
+                // Answers for a previous selection are outdated
+                Ok(_)
+                    if self
+                        .choices
+                        .selected_minecraft_version
+                        .as_ref()
+                        .is_some_and(|v| v.version != minecraft_version) => {}
+                Ok(versions) => self.choices.set_loader_versions(versions),
+                Err(error) => return Message::Error(error).into(),
+            },
             Message::BrowseClientLocation => {
                 let mut dialog = FileDialog::new();
-                if self.client_location.is_dir() {
-                    dialog = dialog.set_directory(&self.client_location);
+                if self.choices.client_location.is_dir() {
+                    dialog = dialog.set_directory(&self.choices.client_location);
                 } else if let Ok(working_dir) = &std::env::current_dir() {
                     dialog = dialog.set_directory(working_dir)
                 }
                 if let Some(path) = dialog.pick_folder() {
-                    self.client_location = path;
+                    self.choices.client_location = path;
                 }
             }
             Message::BrowseServerLocation => {
                 let mut dialog = FileDialog::new();
-                if self.client_location.is_dir() {
-                    dialog = dialog.set_directory(&self.server_location);
+                if self.choices.client_location.is_dir() {
+                    dialog = dialog.set_directory(&self.choices.server_location);
                 } else if let Ok(working_dir) = &std::env::current_dir() {
                     dialog = dialog.set_directory(working_dir)
                 }
 
                 if let Some(path) = dialog.pick_folder() {
-                    self.server_location = path;
+                    self.choices.server_location = path;
                 }
             }
             Message::Install => {
+                if let Err(error) = self.choices.selected_versions() {
+                    return Message::Error(error).into();
+                }
+
                 self.is_installing = true;
                 self.progress = 0.0;
 
-                return match self.installation_type {
-                    Installation::Client => Command::perform(
-                        install_client(
-                            self.client.clone(),
-                            ClientInstallation {
-                                minecraft_version: match &self.selected_minecraft_version {
-                                    Some(s) => s.clone(),
-                                    None => {
-                                        return Message::Error(anyhow!(
-                                            "Minecraft version not selected!"
-                                        ))
-                                        .into()
-                                    }
-                                },
-                                loader_version: match &self.selected_loader_version {
-                                    Some(s) => s.clone(),
-                                    None => {
-                                        return Message::Error(anyhow!(
-                                            "Loader version not selected!"
-                                        ))
-                                        .into()
-                                    }
-                                },
-                                install_dir: self.client_location.clone(),
-                                generate_profile: self.generate_profile,
-                            },
-                        ),
-                        Message::DoneInstalling,
-                    ),
-                    Installation::Server => Command::perform(
-                        install_server(
-                            self.client.clone(),
-                            ServerInstallation {
-                                minecraft_version: match &self.selected_minecraft_version {
-                                    Some(s) => s.clone(),
-                                    None => {
-                                        return Message::Error(anyhow!(
-                                            "Minecraft version not selected!"
-                                        ))
-                                        .into()
-                                    }
-                                },
-                                loader_version: match &self.selected_loader_version {
-                                    Some(s) => s.clone(),
-                                    None => {
-                                        return Message::Error(anyhow!(
-                                            "Loader version not selected!"
-                                        ))
-                                        .into()
-                                    }
-                                },
-                                install_dir: self.server_location.clone(),
-                                download_jar: self.download_server_jar,
-                                generate_script: self.generate_launch_script,
-                            },
-                        ),
-                        Message::DoneInstalling,
-                    ),
-                };
+                return Command::perform(
+                    self.choices.clone().install(self.client.clone()),
+                    Message::DoneInstalling,
+                );
             }
             Message::DoneInstalling(res) => {
                 self.is_installing = false;
@@ -311,11 +377,50 @@ impl Application for State {
                     return Message::Error(e).into();
                 }
             }
+            Message::CheckMods => {
+                let (minecraft_version, loader_version) = match self.choices.selected_versions() {
+                    Ok(versions) => versions,
+                    Err(error) => return Message::Error(error).into(),
+                };
+                let mods_dir = self.choices.mods_dir();
+
+                return Command::perform(
+                    async move { check_mods(&mods_dir, &minecraft_version, &loader_version) },
+                    Message::SetModsReport,
+                );
+            }
+            Message::SetModsReport(result) => match result {
+                Ok(report) => self.mods_report = Some(report.to_string()),
+                Err(error) => return Message::Error(error).into(),
+            },
+            Message::AutoLoaderVersion => {
+                let mods_dir = self.choices.mods_dir();
+                let loader_versions = self.choices.loader_versions.clone();
+                let channel = self.choices.loader_channel;
+                return Command::perform(
+                    async move { select_loader_version(&mods_dir, loader_versions, channel) },
+                    Message::SetAutoLoaderVersion,
+                );
+            }
+            Message::SetAutoLoaderVersion(result) => match result {
+                Ok(version) => {
+                    if version.channel() < self.choices.loader_channel {
+                        self.choices.loader_channel = version.channel();
+                    }
+                    self.choices.selected_loader_version = Some(version);
+                }
+                Err(error) => return Message::Error(error).into(),
+            },
             Message::Error(error) => {
                 eprintln!("{error:?}");
+                let description = match error.chain().find_map(|e| e.downcast_ref::<InstallError>())
+                {
+                    Some(install_error) => format!("{error:#}\n\n{}", install_error.hint()),
+                    None => format!("{error:#}"),
+                };
                 MessageDialog::new()
                     .set_title("Quilt Installer Error")
-                    .set_description(error.to_string())
+                    .set_description(description)
                     .set_level(MessageLevel::Error)
                     .show();
             }
@@ -325,17 +430,34 @@ impl Application for State {
     }
 
     fn view(&self) -> Element<'_, Self::Message> {
+        if let Some(report) = &self.mods_report {
+            let back_label = Text::new("Back")
+                .horizontal_alignment(Horizontal::Center)
+                .width(Length::Fill);
+            let column = Column::new()
+                .padding(5)
+                .spacing(5)
+                .push(Text::new("Mod compatibility").size(20))
+                .push(Scrollable::new(Text::new(report)).height(Length::Fill))
+                .push(
+                    Button::new(back_label)
+                        .width(Length::Fill)
+                        .on_press(Interaction::CloseModsReport),
+                );
+            return Element::from(column).map(Message::Interaction);
+        }
+
         let installation_label = Text::new("Installation:").width(140);
         let installation_client = Radio::new(
             "Client",
             Installation::Client,
-            Some(self.installation_type),
+            Some(self.choices.installation_type),
             Interaction::SelectInstallation,
         );
         let installation_server = Radio::new(
             "Server",
             Installation::Server,
-            Some(self.installation_type),
+            Some(self.choices.installation_type),
             Interaction::SelectInstallation,
         );
         let installation_row = Row::new()
@@ -349,16 +471,40 @@ impl Application for State {
         let minecraft_version_label = Text::new("Minecraft version:").width(140);
         let minecraft_version_list = PickList::new(
             Cow::from_iter(
-                self.minecraft_versions
-                    .iter()
-                    .filter(|v| self.show_snapshots || v.stable)
-                    .cloned(),
+                self.choices
+                    .visible_minecraft_versions()
+                    .cloned()
+                    .map(MinecraftVersionLabel),
             ),
-            self.selected_minecraft_version.clone(),
-            Interaction::SelectMcVersion,
+            self.choices
+                .selected_minecraft_version
+                .clone()
+                .map(MinecraftVersionLabel),
+            |label| Interaction::SelectMcVersion(label.0),
         )
         .width(200);
-        let mut enable_snapshots = Checkbox::new("Show snapshots", self.show_snapshots);
+        let minecraft_version_details = self
+            .choices
+            .selected_minecraft_version
+            .as_ref()
+            .map(|v| {
+                let mut details = v.release_type.to_string();
+                if let Some(time) = v.release_time {
+                    details += &format!(", released {}", time.format("%Y-%m-%d"));
+                }
+                if let Some(java) = v.java_version {
+                    details += &format!(", needs Java {java}");
+                }
+                details
+            })
+            .unwrap_or_default();
+        let minecraft_version_list = Tooltip::new(
+            minecraft_version_list,
+            Text::new(minecraft_version_details),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+        let mut enable_snapshots = Checkbox::new("Show snapshots", self.choices.show_snapshots);
         if !self.is_installing {
             enable_snapshots = enable_snapshots.on_toggle(Interaction::SetShowSnapshots);
         }
@@ -373,25 +519,28 @@ impl Application for State {
 
         let loader_version_label = Text::new("Loader version:").width(140);
         let loader_version_list = PickList::new(
-            Cow::from_iter(
-                self.loader_versions
-                    .iter()
-                    .filter(|v| self.show_betas || v.version.pre.is_empty())
-                    .cloned(),
-            ),
-            self.selected_loader_version.clone(),
+            Cow::from_iter(self.choices.visible_loader_versions().cloned()),
+            self.choices.selected_loader_version.clone(),
             Interaction::SelectLoaderVersion,
         )
         .width(200);
-        let mut enable_betas = Checkbox::new("Show betas", self.show_betas);
+        let loader_channel_list = PickList::new(
+            &LoaderChannel::ALL[..],
+            Some(self.choices.loader_channel),
+            Interaction::SetLoaderChannel,
+        )
+        .width(90);
+        let mut auto_loader_version = Button::new(Text::new("Auto"));
         if !self.is_installing {
-            enable_betas = enable_betas.on_toggle(Interaction::SetShowBetas);
+            auto_loader_version = auto_loader_version.on_press(Interaction::AutoLoaderVersion);
         }
         let loader_row = Row::new()
             .push(loader_version_label)
             .push(loader_version_list)
             .push(Space::new(20, 0))
-            .push(enable_betas)
+            .push(loader_channel_list)
+            .push(Space::new(20, 0))
+            .push(auto_loader_version)
             .width(Length::Fill)
             .spacing(5)
             .padding(5);
@@ -399,7 +548,7 @@ impl Application for State {
         let client_location_label = Text::new("Directory:").width(140);
         let mut client_location_input = TextInput::new(
             "Install location",
-            &self.client_location.display().to_string(),
+            &self.choices.client_location.display().to_string(),
         )
         .padding(5);
         if !self.is_installing {
@@ -418,20 +567,27 @@ impl Application for State {
 
         let client_options_label = Text::new("Options:").width(140);
 
-        let mut create_profile = Checkbox::new("Generate profile", self.generate_profile);
+        let mut create_profile = Checkbox::new("Generate profile", self.choices.generate_profile);
         if !self.is_installing {
             create_profile = create_profile.on_toggle(Interaction::GenerateProfile);
         }
+        let mut download_libraries =
+            Checkbox::new("Download libraries", self.choices.download_libraries);
+        if !self.is_installing {
+            download_libraries = download_libraries.on_toggle(Interaction::DownloadLibraries);
+        }
         let client_options_row = Row::new()
             .push(client_options_label)
             .push(create_profile)
+            .push(Space::new(35, 0))
+            .push(download_libraries)
             .spacing(5)
             .padding(5);
 
         let server_location_label = Text::new("Directory:").width(140);
         let mut server_location_input = TextInput::new(
             "Install location",
-            &self.server_location.display().to_string(),
+            &self.choices.server_location.display().to_string(),
         )
         .padding(5);
         if !self.is_installing {
@@ -450,12 +606,14 @@ impl Application for State {
 
         let server_options_label = Text::new("Options:").width(140);
         let mut download_server_jar =
-            Checkbox::new("Download server jar", self.download_server_jar);
+            Checkbox::new("Download server jar", self.choices.download_server_jar);
         if !self.is_installing {
             download_server_jar = download_server_jar.on_toggle(Interaction::DownloadServerJar);
         }
-        let mut generate_launch_script =
-            Checkbox::new("Generate launch script", self.generate_launch_script);
+        let mut generate_launch_script = Checkbox::new(
+            "Generate launch script",
+            self.choices.generate_launch_script,
+        );
         if !self.is_installing {
             generate_launch_script =
                 generate_launch_script.on_toggle(Interaction::GenerateLaunchScript);
@@ -468,6 +626,23 @@ impl Application for State {
             .spacing(5)
             .padding(5);
 
+        let mods_label = Text::new("Mods:").width(140);
+        let mut install_qfapi = Checkbox::new("Install QFAPI", self.choices.install_qfapi);
+        if !self.is_installing {
+            install_qfapi = install_qfapi.on_toggle(Interaction::InstallQfapi);
+        }
+        let mut check_mods = Button::new(Text::new("Check mods..."));
+        if !self.is_installing {
+            check_mods = check_mods.on_press(Interaction::CheckMods);
+        }
+        let mods_row = Row::new()
+            .push(mods_label)
+            .push(install_qfapi)
+            .push(Space::new(35, 0))
+            .push(check_mods)
+            .spacing(5)
+            .padding(5);
+
         let mut column = Column::new()
             .padding(5)
             .spacing(5)
@@ -476,10 +651,11 @@ impl Application for State {
             .push(loader_row)
             .push(Rule::horizontal(5));
 
-        column = match self.installation_type {
+        column = match self.choices.installation_type {
             Installation::Client => column.push(client_location_row).push(client_options_row),
             Installation::Server => column.push(server_location_row).push(server_options_row),
         };
+        column = column.push(mods_row);
 
         let button_label = Text::new("Install")
             .horizontal_alignment(Horizontal::Center)
@@ -0,0 +1,123 @@
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, derive_more::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum HashFormat {
+    #[display(fmt = "sha1")]
+    Sha1,
+    #[display(fmt = "sha256")]
+    Sha256,
+    #[display(fmt = "sha512")]
+    Sha512,
+    #[display(fmt = "md5")]
+    Md5,
+}
+
+impl HashFormat {
+    /// Hashes `bytes`, returning the lowercase hex digest
+    pub fn digest(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Sha1 => format!("{:x}", Sha1::digest(bytes)),
+            Self::Sha256 => format!("{:x}", Sha256::digest(bytes)),
+            Self::Sha512 => format!("{:x}", Sha512::digest(bytes)),
+            Self::Md5 => format!("{:x}", Md5::digest(bytes)),
+        }
+    }
+
+    /// The extension of the file a maven repository serves this hash in
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+            Self::Md5 => "md5",
+        }
+    }
+}
+
+/// An expected hash of some file, serialized as `format:value`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, derive_more::Display)]
+#[serde(try_from = "String", into = "String")]
+#[display(fmt = "{}:{}", format, value)]
+pub struct Checksum {
+    pub format: HashFormat,
+    pub value: String,
+}
+
+impl TryFrom<String> for Checksum {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let (format, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("{s} is not a checksum like sha256:<hex>"))?;
+        let format = [
+            HashFormat::Sha1,
+            HashFormat::Sha256,
+            HashFormat::Sha512,
+            HashFormat::Md5,
+        ]
+        .into_iter()
+        .find(|f| f.extension() == format)
+        .ok_or_else(|| format!("unknown hash format {format}"))?;
+        Ok(Self::new(format, value))
+    }
+}
+
+impl From<Checksum> for String {
+    fn from(checksum: Checksum) -> Self {
+        checksum.to_string()
+    }
+}
+
+impl Checksum {
+    pub fn new(format: HashFormat, value: impl Into<String>) -> Self {
+        Self {
+            format,
+            value: value.into(),
+        }
+    }
+
+    /// The checksum of `bytes`
+    pub fn of(format: HashFormat, bytes: &[u8]) -> Self {
+        Self::new(format, format.digest(bytes))
+    }
+
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        self.format
+            .digest(bytes)
+            .eq_ignore_ascii_case(self.value.trim())
+    }
+
+    /// Checks that `bytes` match this checksum, naming `artifact` in the error otherwise
+    pub fn verify(&self, artifact: &str, bytes: &[u8]) -> Result<(), HashMismatch> {
+        let actual = self.format.digest(bytes);
+        if !actual.eq_ignore_ascii_case(self.value.trim()) {
+            return Err(HashMismatch {
+                artifact: artifact.into(),
+                format: self.format,
+                expected: self.value.trim().into(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, derive_more::Display, derive_more::Error)]
+#[display(
+    fmt = "{} mismatch for {}: expected {}, got {}",
+    format,
+    artifact,
+    expected,
+    actual
+)]
+pub struct HashMismatch {
+    pub artifact: String,
+    pub format: HashFormat,
+    pub expected: String,
+    pub actual: String,
+}
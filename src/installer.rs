@@ -1,17 +1,26 @@
 use std::{
     collections::HashMap,
     fs::{self, File},
-    io::{Seek, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use anyhow::{anyhow, bail, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use chrono::{DateTime, Utc};
-use reqwest::Client;
+use log::{debug, info};
+use reqwest::{header::LAST_MODIFIED, Client, StatusCode};
 use semver::Version;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{Map, Value};
+use tokio::task::JoinSet;
+
+use crate::error::InstallError;
+use crate::fixups;
+use crate::hash::{Checksum, HashFormat};
+use crate::lock::{self, ArtifactKind, LockedArtifact};
+use crate::maven::{self, LibraryState, MavenArtifact};
+use crate::mojang::{JavaRequirement, ReleaseType, VersionManifest};
+use crate::profile::LaunchProfile;
+use crate::suggest;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Installation {
@@ -26,16 +35,36 @@ pub struct ClientInstallation {
     pub loader_version: LoaderVersion,
     pub install_dir: PathBuf,
     pub generate_profile: bool,
+    pub download_libraries: bool,
+    /// Names of fixups not to apply to the launch profile
+    pub disabled_fixups: Vec<String>,
+    /// Artifacts recorded in a lockfile, which the downloads must match
+    pub pinned_artifacts: Vec<LockedArtifact>,
+}
+
+impl ClientInstallation {
+    /// The name of the version directory and launcher profile
+    pub fn profile_name(&self) -> String {
+        format!(
+            "quilt-loader-{}-{}",
+            self.loader_version, self.minecraft_version
+        )
+    }
 }
 
 impl std::fmt::Display for ClientInstallation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Quilt Loader {} for Minecraft {} to {}{}",
+            "Quilt Loader {} for Minecraft {} to {}{}{}",
             self.loader_version,
             self.minecraft_version,
             self.install_dir.display(),
+            if self.download_libraries {
+                " with libraries"
+            } else {
+                ""
+            },
             if self.generate_profile {
                 " and generating profile"
             } else {
@@ -54,14 +83,44 @@ pub struct ServerInstallation {
     pub generate_script: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, derive_more::Display)]
+impl std::fmt::Display for ServerInstallation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Quilt Loader {} for Minecraft {} to {}{}{}",
+            self.loader_version,
+            self.minecraft_version,
+            self.install_dir.display(),
+            if self.download_jar {
+                " with server jar"
+            } else {
+                ""
+            },
+            if self.generate_script {
+                " and generating launch script"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, derive_more::Display)]
 #[display(fmt = "{}", version)]
 pub struct MinecraftVersion {
     pub version: String,
     pub stable: bool,
+    /// Only tells releases from snapshots until filled in by [`fetch_release_details`]
+    #[serde(default)]
+    pub release_type: ReleaseType,
+    #[serde(default)]
+    pub release_time: Option<DateTime<Utc>>,
+    /// The Java major version the game needs, filled in by [`fetch_java_versions`]
+    #[serde(default)]
+    pub java_version: Option<u32>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, derive_more::Display)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, derive_more::Display)]
 #[display(fmt = "{}", version)]
 pub struct LoaderVersion {
     pub separator: char,
@@ -70,22 +129,312 @@ pub struct LoaderVersion {
     pub version: Version,
 }
 
-pub async fn fetch_minecraft_versions(client: Client) -> Result<Vec<MinecraftVersion>> {
-    Ok(client
-        .get("https://meta.quiltmc.org/v3/versions/game")
+impl MinecraftVersion {
+    /// A version known only by its id, without the details meta and Mojang's manifest add
+    pub(crate) fn from_id(id: &str) -> Self {
+        Self {
+            version: id.into(),
+            stable: false,
+            release_type: ReleaseType::default(),
+            release_time: None,
+            java_version: None,
+        }
+    }
+}
+
+impl LoaderVersion {
+    /// A version known only by its number, with the maven coordinates meta would list
+    pub(crate) fn from_version(version: Version) -> Self {
+        Self {
+            separator: '.',
+            build: 0,
+            maven: format!("org.quiltmc:quilt-loader:{version}"),
+            version,
+        }
+    }
+
+    /// The release channel, from the pre-release tag like `beta` in `0.20.0-beta.5`
+    pub fn channel(&self) -> LoaderChannel {
+        if self.version.pre.is_empty() {
+            return LoaderChannel::Stable;
+        }
+        let tag = self
+            .version
+            .pre
+            .as_str()
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match tag.as_str() {
+            "rc" => LoaderChannel::Rc,
+            "pre" => LoaderChannel::Pre,
+            "beta" => LoaderChannel::Beta,
+            // Unknown tags are treated as the least stable
+            _ => LoaderChannel::Alpha,
+        }
+    }
+}
+
+/// How stable a Quilt Loader version is, ordered from least to most stable
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    derive_more::Display,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LoaderChannel {
+    #[display(fmt = "alpha")]
+    Alpha,
+    #[display(fmt = "beta")]
+    Beta,
+    #[display(fmt = "pre")]
+    Pre,
+    #[display(fmt = "rc")]
+    Rc,
+    #[default]
+    #[display(fmt = "stable")]
+    Stable,
+}
+
+impl LoaderChannel {
+    /// All channels, from most to least stable
+    pub const ALL: [Self; 5] = [Self::Stable, Self::Rc, Self::Pre, Self::Beta, Self::Alpha];
+}
+
+impl std::str::FromStr for LoaderChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|c| c.to_string());
+                format!("expected one of {}", names.join(", "))
+            })
+    }
+}
+
+async fn fetch_meta<T: DeserializeOwned>(client: &Client, url: &str) -> Result<T, InstallError> {
+    let response = client
+        .get(url)
         .send()
-        .await?
-        .json()
-        .await?)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(InstallError::network(url))?;
+    response.json().await.map_err(InstallError::network(url))
 }
 
-pub async fn fetch_loader_versions(client: Client) -> Result<Vec<LoaderVersion>> {
-    Ok(client
-        .get("https://meta.quiltmc.org/v3/versions/loader")
+pub async fn fetch_minecraft_versions(
+    client: Client,
+) -> Result<Vec<MinecraftVersion>, InstallError> {
+    let mut versions: Vec<MinecraftVersion> =
+        fetch_meta(&client, "https://meta.quiltmc.org/v3/versions/game").await?;
+    for version in &mut versions {
+        if !version.stable {
+            version.release_type = ReleaseType::Snapshot;
+        }
+    }
+    Ok(versions)
+}
+
+/// Fills in release types and times from Mojang's version manifest at `manifest_url`,
+/// returning the manifest for looking up more details later
+pub async fn fetch_release_details(
+    client: Client,
+    manifest_url: &str,
+    versions: &mut [MinecraftVersion],
+) -> Result<VersionManifest, InstallError> {
+    let manifest: VersionManifest = fetch_meta(&client, manifest_url).await?;
+    for version in versions {
+        if let Some(entry) = manifest.versions.iter().find(|v| v.id == version.version) {
+            version.release_type = entry.release_type();
+            version.release_time = Some(entry.release_time);
+        }
+    }
+    Ok(manifest)
+}
+
+/// How many launch JSONs are downloaded at once
+const CONCURRENT_DOWNLOADS: usize = 16;
+
+/// Fills in the Java versions needed by `versions` from their launch JSONs.
+/// Versions missing from the manifest, or whose launch JSON could not be fetched, are left without one.
+pub async fn fetch_java_versions(
+    client: Client,
+    manifest: &VersionManifest,
+    versions: &mut [MinecraftVersion],
+) {
+    for chunk in versions.chunks_mut(CONCURRENT_DOWNLOADS) {
+        let mut downloads = JoinSet::new();
+        for (i, version) in chunk.iter().enumerate() {
+            let Some(entry) = manifest.versions.iter().find(|v| v.id == version.version) else {
+                continue;
+            };
+            let client = client.clone();
+            let url = entry.url.clone();
+            downloads.spawn(async move {
+                let requirement: Result<JavaRequirement, _> = fetch_meta(&client, &url).await;
+                (i, requirement)
+            });
+        }
+        while let Some(result) = downloads.join_next().await {
+            let (i, requirement) = result.expect("launch JSON download panicked");
+            match requirement {
+                Ok(requirement) => chunk[i].java_version = Some(requirement.major_version()),
+                Err(error) => debug!(
+                    "Could not look up the Java version of {}: {error}",
+                    chunk[i].version
+                ),
+            }
+        }
+    }
+}
+
+pub async fn fetch_loader_versions(client: Client) -> Result<Vec<LoaderVersion>, InstallError> {
+    fetch_meta(&client, "https://meta.quiltmc.org/v3/versions/loader").await
+}
+
+/// An entry of the loader versions meta lists for a Minecraft version
+#[derive(Deserialize)]
+struct CompatibleLoader {
+    loader: LoaderVersion,
+}
+
+/// The Quilt Loader versions that can be installed for a Minecraft version, newest first
+pub async fn fetch_compatible_loader_versions(
+    client: Client,
+    minecraft_version: &str,
+) -> Result<Vec<LoaderVersion>, InstallError> {
+    let loaders: Vec<CompatibleLoader> = fetch_meta(
+        &client,
+        &format!("https://meta.quiltmc.org/v3/versions/loader/{minecraft_version}"),
+    )
+    .await?;
+    Ok(loaders.into_iter().map(|l| l.loader).collect())
+}
+
+/// When a Quilt Loader version was published, from the `Last-Modified` date of its POM
+/// on the Quilt maven, or `None` if the POM is missing or its date unknown
+pub async fn fetch_loader_release_time(
+    client: Client,
+    loader_version: &LoaderVersion,
+) -> Result<Option<DateTime<Utc>>, InstallError> {
+    let Ok(mut artifact) = loader_version.maven.parse::<MavenArtifact>() else {
+        return Ok(None);
+    };
+    artifact.extension = "pom".into();
+    let url = artifact.url(maven::QUILT_REPOSITORY);
+    let response = client
+        .head(&url)
         .send()
+        .await
+        .map_err(InstallError::network(&url))?;
+    // Not every version has a POM, and not every repository answers HEAD requests
+    if !response.status().is_success() {
+        debug!(
+            "No release time for {loader_version}: {url} returned {}",
+            response.status()
+        );
+        return Ok(None);
+    }
+    Ok(response
+        .headers()
+        .get(LAST_MODIFIED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .map(|time| time.with_timezone(&Utc)))
+}
+
+/// The first of `loader_versions` that was published by `as_of`,
+/// so the newest one if they are ordered newest first
+pub async fn find_loader_version_as_of(
+    client: Client,
+    loader_versions: &[LoaderVersion],
+    as_of: DateTime<Utc>,
+) -> Result<Option<LoaderVersion>, InstallError> {
+    for chunk in loader_versions.chunks(CONCURRENT_DOWNLOADS) {
+        let mut lookups = JoinSet::new();
+        for (i, version) in chunk.iter().enumerate() {
+            let client = client.clone();
+            let version = version.clone();
+            lookups.spawn(async move { (i, fetch_loader_release_time(client, &version).await) });
+        }
+        let mut release_times = vec![None; chunk.len()];
+        while let Some(result) = lookups.join_next().await {
+            let (i, release_time) = result.expect("release time lookup panicked");
+            release_times[i] = release_time?;
+        }
+        let released = chunk
+            .iter()
+            .zip(release_times)
+            .find(|(_, time)| time.is_some_and(|time| time <= as_of));
+        if let Some((version, _)) = released {
+            return Ok(Some(version.clone()));
+        }
+    }
+    Ok(None)
+}
+
+/// Looks up the exact Minecraft and Quilt Loader versions named by e.g. a modpack,
+/// making sure they can be installed together
+pub async fn find_versions(
+    client: Client,
+    minecraft_version: &str,
+    loader_version: &str,
+) -> Result<(MinecraftVersion, LoaderVersion), InstallError> {
+    let minecraft_versions = fetch_minecraft_versions(client.clone()).await?;
+    let Some(minecraft) = minecraft_versions
+        .iter()
+        .find(|v| v.version == minecraft_version)
+    else {
+        return Err(InstallError::MetaNotFound {
+            what: format!("Minecraft version {minecraft_version}"),
+            suggestions: suggest::did_you_mean(
+                minecraft_version,
+                minecraft_versions.iter().map(|v| v.version.as_str()),
+            ),
+        });
+    };
+
+    let loader_versions =
+        fetch_compatible_loader_versions(client.clone(), minecraft_version).await?;
+    if let Some(loader) = loader_versions
+        .iter()
+        .find(|v| v.to_string() == loader_version)
+    {
+        return Ok((minecraft.clone(), loader.clone()));
+    }
+
+    if fetch_loader_versions(client)
         .await?
-        .json()
-        .await?)
+        .iter()
+        .any(|v| v.to_string() == loader_version)
+    {
+        Err(InstallError::UnsupportedVersion {
+            minecraft_version: minecraft_version.into(),
+            loader_version: loader_version.into(),
+        })
+    } else {
+        let names = loader_versions
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>();
+        Err(InstallError::MetaNotFound {
+            what: format!("Quilt Loader version {loader_version}"),
+            suggestions: suggest::did_you_mean(loader_version, names.iter().map(String::as_str)),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -96,6 +445,88 @@ pub struct LauncherProfiles {
     other: Map<String, Value>,
 }
 
+impl LauncherProfiles {
+    pub fn read(install_dir: &Path) -> Result<Self, InstallError> {
+        let path = install_dir.join("launcher_profiles.json");
+        let file = File::open(&path).map_err(InstallError::io(&path))?;
+        serde_json::from_reader(file).map_err(|error| InstallError::CorruptProfile {
+            origin: path.display().to_string(),
+            reason: error.to_string(),
+        })
+    }
+
+    pub fn write(&self, install_dir: &Path) -> Result<(), InstallError> {
+        let path = install_dir.join("launcher_profiles.json");
+        let file = File::create(&path).map_err(InstallError::io(&path))?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|error| InstallError::io(&path)(error.into()))
+    }
+
+    /// Keys of the Quilt profiles whose version is not installed
+    pub fn dangling_profiles(&self, install_dir: &Path) -> Vec<String> {
+        let mut dangling = self
+            .profiles
+            .iter()
+            .filter(|(_, p)| {
+                p.last_version_id.starts_with("quilt-loader-")
+                    && !install_dir
+                        .join("versions")
+                        .join(&p.last_version_id)
+                        .join(format!("{}.json", p.last_version_id))
+                        .exists()
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        dangling.sort();
+        dangling
+    }
+
+    pub fn remove_profile(&mut self, key: &str) {
+        self.profiles.remove(key);
+    }
+
+    pub fn contains_profile(&self, key: &str) -> bool {
+        self.profiles.contains_key(key)
+    }
+}
+
+/// Whether installing would change nothing: the launch profile matches what meta serves with
+/// the same fixups, the libraries are in place if wanted, and so is the launcher profile
+pub async fn is_client_installed(
+    client: &Client,
+    args: &ClientInstallation,
+) -> Result<bool, InstallError> {
+    let profile_name = args.profile_name();
+    let launch_json = args
+        .install_dir
+        .join("versions")
+        .join(&profile_name)
+        .join(format!("{profile_name}.json"));
+    let Ok(installed) = fs::read_to_string(&launch_json) else {
+        return Ok(false);
+    };
+    if args.generate_profile
+        && !LauncherProfiles::read(&args.install_dir)
+            .is_ok_and(|profiles| profiles.contains_profile(&profile_name))
+    {
+        return Ok(false);
+    }
+
+    let (_, response) = fetch_launch_profile(client, args).await?;
+    let launch_profile = fixed_launch_profile(args, &response)?;
+    if installed != launch_profile.to_json() {
+        return Ok(false);
+    }
+    if args.download_libraries {
+        let libraries_dir = args.install_dir.join("libraries");
+        let libraries = maven::resolve_libraries(client, &launch_profile).await?;
+        return Ok(libraries
+            .iter()
+            .all(|library| library.state(&libraries_dir) == LibraryState::Present));
+    }
+    Ok(true)
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct Profile {
@@ -127,83 +558,135 @@ pub fn get_default_client_directory() -> PathBuf {
     PathBuf::from(std::env::var("HOME").unwrap()).join(".minecraft")
 }
 
-pub async fn install_client(client: Client, args: ClientInstallation) -> Result<()> {
-    println!("Installing client {args}");
+/// Downloads the launch profile for the versions from meta, returning its URL and contents
+pub(crate) async fn fetch_launch_profile(
+    client: &Client,
+    args: &ClientInstallation,
+) -> Result<(String, String), InstallError> {
+    let url = format!(
+        "https://meta.quiltmc.org/v3/versions/loader/{}/{}/profile/json",
+        &args.minecraft_version.version, &args.loader_version.version
+    );
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(InstallError::network(&url))?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(InstallError::UnsupportedVersion {
+            minecraft_version: args.minecraft_version.version.clone(),
+            loader_version: args.loader_version.to_string(),
+        });
+    }
+    let response = match response.error_for_status() {
+        Ok(response) => response.text().await,
+        Err(error) => Err(error),
+    }
+    .map_err(InstallError::network(&url))?;
+    Ok((url, response))
+}
+
+/// Parses the launch profile and works around known problems in it
+pub(crate) fn fixed_launch_profile(
+    args: &ClientInstallation,
+    response: &str,
+) -> Result<LaunchProfile, InstallError> {
+    let mut launch_profile = LaunchProfile::parse(response, "Quilt meta")?;
+    for name in fixups::apply_fixups(
+        &mut launch_profile,
+        &args.minecraft_version,
+        &args.loader_version,
+        &args.disabled_fixups,
+    ) {
+        debug!("Applied fixup {name}");
+    }
+    Ok(launch_profile)
+}
+
+/// Installs the client, returning the artifacts it is made of for recording in a lockfile
+pub async fn install_client(
+    client: Client,
+    args: ClientInstallation,
+) -> Result<Vec<LockedArtifact>, InstallError> {
+    info!("Installing client {args}");
 
     // Verify install location
     if !args.install_dir.join("launcher_profiles.json").exists() {
-        bail!(
-            "{} is not a valid installation directory",
-            args.install_dir.display(),
-        );
+        return Err(InstallError::InvalidInstallDir {
+            path: args.install_dir,
+        });
     }
 
     // Resolve profile directory
-    let profile_name = format!(
-        "quilt-loader-{}-{}",
-        args.loader_version, args.minecraft_version
-    );
+    let profile_name = args.profile_name();
     let profile_dir = args.install_dir.join("versions").join(&profile_name);
 
+    // Download launch json
+    let (url, response) = fetch_launch_profile(&client, &args).await?;
+    // Meta publishes no checksums for profiles, so this is only recorded for the lockfile,
+    // unless one is being installed and pins it
+    let checksum = Checksum::of(HashFormat::Sha256, response.as_bytes());
+    let checksum = if args.pinned_artifacts.is_empty() {
+        checksum
+    } else {
+        let locked =
+            lock::pinned_checksum(&args.pinned_artifacts, "the launch profile", &url, checksum)?;
+        locked.verify("the launch profile", response.as_bytes())?;
+        locked
+    };
+    let mut artifacts = vec![LockedArtifact {
+        kind: ArtifactKind::Profile,
+        name: profile_name.clone(),
+        url,
+        checksum,
+    }];
+    let launch_profile = fixed_launch_profile(&args, &response)?;
+
     // Delete existing profile
     if profile_dir.exists() {
-        fs::remove_dir_all(&profile_dir)?;
+        fs::remove_dir_all(&profile_dir).map_err(InstallError::io(&profile_dir))?;
     }
 
     // Create profile directory
-    fs::create_dir_all(&profile_dir)?;
+    fs::create_dir_all(&profile_dir).map_err(InstallError::io(&profile_dir))?;
 
     // Create launch json
-    let mut file = File::create(profile_dir.join(profile_name.clone() + ".json"))?;
+    let path = profile_dir.join(profile_name.clone() + ".json");
+    fs::write(&path, launch_profile.to_json()).map_err(InstallError::io(&path))?;
 
-    // Download launch json
-    let mut response = client
-        .get(format!(
-            "https://meta.quiltmc.org/v3/versions/loader/{}/{}/profile/json",
-            &args.minecraft_version.version, &args.loader_version.version
-        ))
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    // Hack-Fix:
-    // Was fixed in versions above 0.17.7
-    if args.loader_version.version < Version::new(0, 17, 7) {
-        // Quilt-meta specifies both hashed and intermediary,
-        // but providing both to quilt-loader causes it to silently fail remapping.
-        let mut json: Value = serde_json::from_str(&response)?;
-        let libs = json
-            .as_object_mut()
-            .unwrap()
-            .get_mut("libraries")
-            .unwrap()
-            .as_array_mut()
-            .unwrap();
-        libs.retain(|lib| {
-            !lib.as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .starts_with("org.quiltmc:hashed")
-        });
-        response = serde_json::to_string(&json)?;
+    // Download libraries
+    if args.download_libraries {
+        let mut libraries = maven::resolve_libraries(&client, &launch_profile).await?;
+        for library in &mut libraries {
+            library.checksum = lock::pinned_checksum(
+                &args.pinned_artifacts,
+                &library.artifact.to_string(),
+                &library.url,
+                library.checksum.clone(),
+            )?;
+            artifacts.push(LockedArtifact {
+                kind: ArtifactKind::Library,
+                name: library.artifact.to_string(),
+                url: library.url.clone(),
+                checksum: library.checksum.clone(),
+            });
+        }
+        let report =
+            maven::download_libraries(&client, libraries, &args.install_dir.join("libraries"))
+                .await?;
+        for artifact in &report.fetched {
+            debug!("Downloaded library {artifact}");
+        }
+        info!(
+            "Fetched {} libraries, {} were already present",
+            report.fetched.len(),
+            report.present.len()
+        );
     }
-    // End of hack-fix
-
-    file.write_all(response.as_bytes())?;
 
     // Generate profile
     if args.generate_profile {
-        let mut file = fs::OpenOptions::new().read(true).write(true).open(
-            args.install_dir
-                .join("launcher_profiles")
-                .with_extension("json"),
-        )?;
-
-        let mut launcher_profiles: LauncherProfiles = serde_json::from_reader(&file)?;
+        let mut launcher_profiles = LauncherProfiles::read(&args.install_dir)?;
         launcher_profiles.profiles.insert(
             profile_name.clone(),
             Profile {
@@ -215,17 +698,16 @@ pub async fn install_client(client: Client, args: ClientInstallation) -> Result<
                 other: Map::new(),
             },
         );
-
-        file.set_len(0)?;
-        file.rewind()?;
-        serde_json::to_writer_pretty(file, &launcher_profiles)?;
+        launcher_profiles.write(&args.install_dir)?;
     }
 
-    println!("Client installed successfully.");
-    Ok(())
+    info!("Client installed successfully.");
+    Ok(artifacts)
 }
 
-pub async fn install_server(_client: Client, args: ServerInstallation) -> Result<()> {
-    println!("Installing server\n{args:#?}");
-    Err(anyhow!("Server installation hasn't been implemented!"))
+pub async fn install_server(_client: Client, args: ServerInstallation) -> Result<(), InstallError> {
+    info!("Installing server {args}");
+    Err(InstallError::Unsupported {
+        what: "Server installation".into(),
+    })
 }
@@ -0,0 +1,42 @@
+//! Installation of Quilt Loader for Minecraft clients and servers.
+//!
+//! The `quilt-installer` binary is a thin CLI and GUI on top of this library.
+//! Progress is reported through the [`log`] crate, so embedders choose where it goes.
+
+pub mod config;
+pub mod curseforge;
+mod download;
+mod error;
+pub mod fixups;
+pub(crate) mod hash;
+pub mod installer;
+pub mod lock;
+pub(crate) mod maven;
+pub mod modrinth;
+pub mod mods;
+pub mod mojang;
+pub mod packwiz;
+pub(crate) mod profile;
+pub(crate) mod suggest;
+#[cfg(test)]
+mod temp_dir;
+pub mod verify;
+pub(crate) mod version_range;
+
+pub use error::InstallError;
+pub use hash::{Checksum, HashFormat, HashMismatch};
+pub use installer::{
+    fetch_compatible_loader_versions, fetch_java_versions, fetch_loader_versions,
+    fetch_minecraft_versions, fetch_release_details, find_loader_version_as_of, find_versions,
+    install_client, install_server, ClientInstallation, Installation, LauncherProfiles,
+    LoaderChannel, LoaderVersion, MinecraftVersion, ServerInstallation,
+};
+pub use lock::LockedArtifact;
+pub use maven::{LibraryState, MavenArtifact, ResolvedLibrary};
+pub use mojang::{ReleaseType, VersionManifest};
+pub use profile::{Arguments, LaunchProfile, Library};
+pub use suggest::suggest_versions;
+pub use version_range::{FlexVersion, VersionRange};
+
+/// The Quilt logo, used as the icon of generated launcher profiles
+pub const ICON: &[u8] = include_bytes!("../quilt.png");
@@ -0,0 +1,120 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::InstallError;
+use crate::hash::{Checksum, HashMismatch};
+use crate::installer::{LoaderVersion, MinecraftVersion};
+
+/// The lockfile format written by this version of the installer
+const LOCK_VERSION: u32 = 1;
+
+/// An installation recorded with its exact versions and artifacts, to reproduce it elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub lock_version: u32,
+    pub options: LockedOptions,
+    pub minecraft_version: MinecraftVersion,
+    pub loader_version: LoaderVersion,
+    pub artifacts: Vec<LockedArtifact>,
+}
+
+/// The client options the installation was made with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedOptions {
+    pub generate_profile: bool,
+    pub download_libraries: bool,
+    #[serde(default)]
+    pub disabled_fixups: Vec<String>,
+    pub qfapi: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    /// The launch profile from Quilt meta
+    Profile,
+    Library,
+    Mod,
+}
+
+/// A downloaded file and the hash it must have
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedArtifact {
+    pub kind: ArtifactKind,
+    pub name: String,
+    pub url: String,
+    pub checksum: Checksum,
+}
+
+impl Lockfile {
+    pub fn new(
+        options: LockedOptions,
+        minecraft_version: MinecraftVersion,
+        loader_version: LoaderVersion,
+        artifacts: Vec<LockedArtifact>,
+    ) -> Self {
+        Self {
+            lock_version: LOCK_VERSION,
+            options,
+            minecraft_version,
+            loader_version,
+            artifacts,
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, InstallError> {
+        let contents = fs::read_to_string(path).map_err(InstallError::io(path))?;
+        let lockfile: Self =
+            toml::from_str(&contents).map_err(|error| InstallError::InvalidLockfile {
+                path: path.to_owned(),
+                reason: error.message().to_owned(),
+            })?;
+        if lockfile.lock_version > LOCK_VERSION {
+            return Err(InstallError::InvalidLockfile {
+                path: path.to_owned(),
+                reason: "it was written by a newer version of the installer".into(),
+            });
+        }
+        Ok(lockfile)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), InstallError> {
+        let contents = toml::to_string_pretty(self).expect("lockfiles always serialize");
+        fs::write(path, contents).map_err(InstallError::io(path))
+    }
+
+    pub fn artifacts(&self, kind: ArtifactKind) -> impl Iterator<Item = &LockedArtifact> {
+        self.artifacts.iter().filter(move |a| a.kind == kind)
+    }
+}
+
+/// The checksum `pinned` requires for `url`, failing if `actual` is of the same format and differs.
+/// Returns `actual` when nothing is pinned, so installs without a lockfile are unaffected.
+pub(crate) fn pinned_checksum(
+    pinned: &[LockedArtifact],
+    name: &str,
+    url: &str,
+    actual: Checksum,
+) -> Result<Checksum, InstallError> {
+    if pinned.is_empty() {
+        return Ok(actual);
+    }
+    let Some(locked) = pinned.iter().find(|a| a.url == url) else {
+        return Err(InstallError::NotLocked {
+            artifact: name.into(),
+        });
+    };
+    if locked.checksum.format == actual.format
+        && !locked.checksum.value.eq_ignore_ascii_case(&actual.value)
+    {
+        return Err(HashMismatch {
+            artifact: name.into(),
+            format: actual.format,
+            expected: locked.checksum.value.clone(),
+            actual: actual.value,
+        }
+        .into());
+    }
+    Ok(locked.checksum.clone())
+}
@@ -1,16 +1,86 @@
-#![windows_subsystem = "windows"]
+#![cfg_attr(feature = "gui", windows_subsystem = "windows")]
 
-use anyhow::Context;
-use clap::Parser;
+use std::process::ExitCode;
 
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use quilt_installer::InstallError;
+
+mod apply;
+mod choices;
 mod cli;
+#[cfg(feature = "gui")]
 mod gui;
-mod installer;
+mod wizard;
+
+/// Prints the library's log messages, warnings and errors going to stderr
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("quilt_installer")
+    }
 
-const ICON: &[u8] = include_bytes!("../quilt.png");
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            Level::Error | Level::Warn => eprintln!("{}", record.args()),
+            _ => println!("{}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(client: reqwest::Client, manifest_url: String) -> anyhow::Result<()> {
+    if !gui::display_available() {
+        println!("No display found, continuing in the terminal");
+        return run_wizard(client);
+    }
+
+    println!("quilt-installer can also be used as a CLI! Run with --help for more information");
+    match gui::run(client.clone(), manifest_url) {
+        Err(error) if gui::is_window_error(&error) => {
+            eprintln!("{error:#}, continuing in the terminal");
+            run_wizard(client)
+        }
+        result => result,
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_wizard(client: reqwest::Client) -> anyhow::Result<()> {
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(wizard::run(client))
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_client: reqwest::Client, _manifest_url: String) -> anyhow::Result<()> {
+    use clap::{error::ErrorKind, CommandFactory};
+
+    cli::Args::command()
+        .error(
+            ErrorKind::MissingSubcommand,
+            "this build of quilt-installer has no GUI, use the wizard or another subcommand",
+        )
+        .exit()
+}
+
+fn main() -> ExitCode {
+    let (args, matches) = cli::Args::parse_with_env();
+    log::set_logger(&Logger).unwrap();
+    log::set_max_level(if args.verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    });
+    if args.verbose {
+        cli::print_value_sources(&matches);
+    }
 
-fn main() -> anyhow::Result<()> {
-    let args = cli::Args::parse();
     let client = reqwest::Client::builder()
         .user_agent(concat!(
             env!("CARGO_PKG_NAME"),
@@ -20,13 +90,25 @@ fn main() -> anyhow::Result<()> {
         .build()
         .unwrap();
 
-    if args.subcommand.is_some() {
+    let result = if args.subcommand.is_some() {
         tokio::runtime::Runtime::new()
             .unwrap()
             .block_on(cli::cli(client, args))
-            .context("Installation failed!")
     } else {
-        println!("quilt-installer can also be used as a CLI! Run with --help for more information");
-        gui::run(client)
+        run_gui(client, args.manifest_url)
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            match error.chain().find_map(|e| e.downcast_ref::<InstallError>()) {
+                Some(error) => {
+                    eprintln!("\n{}", error.hint());
+                    ExitCode::from(error.exit_code())
+                }
+                None => ExitCode::FAILURE,
+            }
+        }
     }
 }
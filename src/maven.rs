@@ -0,0 +1,182 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Result};
+use reqwest::Client;
+
+use crate::download;
+use crate::error::InstallError;
+use crate::hash::Checksum;
+use crate::profile::LaunchProfile;
+
+/// Where libraries without a repository URL come from
+pub const DEFAULT_REPOSITORY: &str = "https://libraries.minecraft.net/";
+
+/// Where Quilt Loader and its libraries are published
+pub const QUILT_REPOSITORY: &str = "https://maven.quiltmc.org/repository/release/";
+
+/// Maven coordinates in the `group:artifact:version[:classifier][@extension]` form
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+#[display(fmt = "{}:{}:{}", group, artifact, version)]
+pub struct MavenArtifact {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub classifier: Option<String>,
+    pub extension: String,
+}
+
+impl FromStr for MavenArtifact {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (coordinates, extension) = s.split_once('@').unwrap_or((s, "jar"));
+        let mut parts = coordinates.split(':');
+        let (Some(group), Some(artifact), Some(version)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            bail!("{s} is not a valid maven artifact");
+        };
+        let classifier = parts.next().map(str::to_owned);
+        if parts.next().is_some() {
+            bail!("{s} is not a valid maven artifact");
+        }
+
+        Ok(Self {
+            group: group.into(),
+            artifact: artifact.into(),
+            version: version.into(),
+            classifier,
+            extension: extension.into(),
+        })
+    }
+}
+
+impl MavenArtifact {
+    /// The path of the artifact inside a repository, using `/` as the separator
+    pub fn path(&self) -> String {
+        format!(
+            "{}/{}/{}/{}-{}{}.{}",
+            self.group.replace('.', "/"),
+            self.artifact,
+            self.version,
+            self.artifact,
+            self.version,
+            self.classifier
+                .as_ref()
+                .map_or_else(String::new, |c| format!("-{c}")),
+            self.extension
+        )
+    }
+
+    pub fn url(&self, repository: &str) -> String {
+        format!("{}/{}", repository.trim_end_matches('/'), self.path())
+    }
+
+    /// Where the artifact lives inside a local repository such as `.minecraft/libraries`
+    pub fn local_path(&self, repository: &Path) -> PathBuf {
+        repository.join(self.path())
+    }
+}
+
+/// A library with its download location and expected hash
+#[derive(Debug, Clone)]
+pub struct ResolvedLibrary {
+    pub artifact: MavenArtifact,
+    pub url: String,
+    pub checksum: Checksum,
+}
+
+/// Resolves the libraries of a launch profile,
+/// fetching their checksums from the repository unless the profile embeds them
+pub async fn resolve_libraries(
+    client: &Client,
+    profile: &LaunchProfile,
+) -> Result<Vec<ResolvedLibrary>, InstallError> {
+    let mut resolved = Vec::new();
+    for library in &profile.libraries {
+        let artifact: MavenArtifact =
+            library
+                .name
+                .parse()
+                .map_err(|error: anyhow::Error| InstallError::CorruptProfile {
+                    origin: profile.id.clone(),
+                    reason: error.to_string(),
+                })?;
+        let url = artifact.url(library.url.as_deref().unwrap_or(DEFAULT_REPOSITORY));
+        let checksum = match library.checksum() {
+            Some(checksum) => checksum,
+            None => download::fetch_maven_checksum(client, &library.name, &url).await?,
+        };
+        resolved.push(ResolvedLibrary {
+            artifact,
+            url,
+            checksum,
+        });
+    }
+
+    Ok(resolved)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibraryState {
+    Present,
+    Missing,
+    Corrupt,
+}
+
+impl ResolvedLibrary {
+    pub fn state(&self, libraries_dir: &Path) -> LibraryState {
+        match fs::read(self.artifact.local_path(libraries_dir)) {
+            Ok(bytes) if self.checksum.matches(&bytes) => LibraryState::Present,
+            Ok(_) => LibraryState::Corrupt,
+            Err(_) => LibraryState::Missing,
+        }
+    }
+
+    pub async fn download(
+        &self,
+        client: &Client,
+        libraries_dir: &Path,
+    ) -> Result<(), InstallError> {
+        let bytes = download::download_verified(
+            client,
+            &self.artifact.to_string(),
+            &self.url,
+            &self.checksum,
+        )
+        .await?;
+        download::place(&self.artifact.local_path(libraries_dir), &bytes)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LibraryReport {
+    pub fetched: Vec<MavenArtifact>,
+    /// Libraries that were already present with the correct hash
+    pub present: Vec<MavenArtifact>,
+}
+
+/// Downloads resolved libraries into `libraries_dir`,
+/// skipping those that are already present with the correct hash
+pub async fn download_libraries(
+    client: &Client,
+    libraries: Vec<ResolvedLibrary>,
+    libraries_dir: &Path,
+) -> Result<LibraryReport, InstallError> {
+    let mut report = LibraryReport::default();
+    for library in libraries {
+        if library.state(libraries_dir) == LibraryState::Present {
+            report.present.push(library.artifact);
+            continue;
+        }
+
+        library.download(client, libraries_dir).await?;
+        report.fetched.push(library.artifact);
+    }
+
+    Ok(report)
+}
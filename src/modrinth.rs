@@ -0,0 +1,121 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::download;
+use crate::hash::{Checksum, HashFormat};
+use crate::installer::MinecraftVersion;
+use crate::lock::{ArtifactKind, LockedArtifact};
+use crate::mods;
+
+pub const DEFAULT_API_URL: &str = "https://api.modrinth.com";
+
+/// Modrinth project slug of Quilted Fabric API
+const QFAPI_PROJECT: &str = "qsl";
+
+/// File name prefixes of QFAPI jars, current and historical
+const QFAPI_PREFIXES: &[&str] = &["qfapi-", "quilted-fabric-api-", "quilted_fabric_api-"];
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProjectVersion {
+    version_number: String,
+    files: Vec<VersionFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct VersionFile {
+    url: String,
+    filename: String,
+    primary: bool,
+    hashes: FileHashes,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FileHashes {
+    sha512: String,
+}
+
+/// Downloads the newest Quilted Fabric API build for `minecraft_version` into `mods_dir`,
+/// replacing any other QFAPI jars. Returns the installed jar for recording in a lockfile.
+pub async fn install_qfapi(
+    client: Client,
+    api_url: &str,
+    minecraft_version: &MinecraftVersion,
+    mods_dir: &Path,
+) -> Result<LockedArtifact> {
+    let artifact = find_qfapi(client.clone(), api_url, minecraft_version).await?;
+    install_locked_qfapi(client, &artifact, mods_dir).await?;
+    Ok(artifact)
+}
+
+/// The newest Quilted Fabric API jar for `minecraft_version`
+pub async fn find_qfapi(
+    client: Client,
+    api_url: &str,
+    minecraft_version: &MinecraftVersion,
+) -> Result<LockedArtifact> {
+    let versions: Vec<ProjectVersion> = client
+        .get(format!(
+            "{}/v2/project/{QFAPI_PROJECT}/version",
+            api_url.trim_end_matches('/')
+        ))
+        .query(&[
+            ("loaders", r#"["quilt"]"#.to_owned()),
+            (
+                "game_versions",
+                format!(r#"["{}"]"#, minecraft_version.version),
+            ),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // Modrinth lists the newest version first
+    let version = versions.first().with_context(|| {
+        format!("Could not find a Quilted Fabric API version for Minecraft {minecraft_version}")
+    })?;
+    let file = version
+        .files
+        .iter()
+        .find(|f| f.primary)
+        .or(version.files.first())
+        .with_context(|| format!("Quilted Fabric API {} has no files", version.version_number))?;
+    debug!("Newest Quilted Fabric API is {}", version.version_number);
+
+    Ok(LockedArtifact {
+        kind: ArtifactKind::Mod,
+        name: file.filename.clone(),
+        url: file.url.clone(),
+        checksum: Checksum::new(HashFormat::Sha512, &file.hashes.sha512),
+    })
+}
+
+/// Downloads a Quilted Fabric API jar recorded in a lockfile into `mods_dir`,
+/// replacing any other QFAPI jars
+pub async fn install_locked_qfapi(
+    client: Client,
+    artifact: &LockedArtifact,
+    mods_dir: &Path,
+) -> Result<()> {
+    info!("Installing Quilted Fabric API {}", artifact.name);
+    let path = mods::mod_path(artifact, mods_dir)?;
+    let contents =
+        download::download_verified(&client, &artifact.name, &artifact.url, &artifact.checksum)
+            .await?;
+
+    fs::create_dir_all(mods_dir)?;
+    for entry in fs::read_dir(mods_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if name.ends_with(".jar") && QFAPI_PREFIXES.iter().any(|p| name.starts_with(p)) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    download::place(&path, &contents)?;
+    Ok(())
+}
@@ -0,0 +1,731 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    fs::{self, File},
+    io::{Cursor, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use reqwest::Client;
+use serde::Deserialize;
+use zip::ZipArchive;
+
+use crate::download;
+use crate::installer::{LoaderChannel, LoaderVersion, MinecraftVersion};
+use crate::lock::LockedArtifact;
+use crate::version_range::VersionRange;
+
+/// Mod IDs that are provided by the game, the loader or the environment
+const BUILTIN_IDS: &[&str] = &["minecraft", "java", "quilt_loader", "fabricloader"];
+
+#[derive(Debug, Clone)]
+pub struct ModInfo {
+    pub id: String,
+    pub version: String,
+    pub file: PathBuf,
+    pub depends: Vec<Dependency>,
+    /// IDs provided by this mod and the mods nested in its jar
+    pub provides: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub id: String,
+    pub versions: VersionRange,
+    pub optional: bool,
+}
+
+#[derive(Deserialize)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader,
+}
+
+#[derive(Deserialize)]
+struct QuiltLoader {
+    id: String,
+    version: String,
+    #[serde(default)]
+    depends: Vec<QuiltDependency>,
+    #[serde(default)]
+    provides: Vec<QuiltProvides>,
+    #[serde(default)]
+    jars: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuiltDependency {
+    Id(String),
+    Object {
+        id: String,
+        versions: Option<QuiltVersions>,
+        #[serde(default)]
+        optional: bool,
+    },
+    /// Any one of the dependencies, which we don't check
+    #[allow(dead_code)]
+    Any(Vec<QuiltDependency>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuiltVersions {
+    Single(String),
+    Any(Vec<QuiltVersions>),
+    Object {
+        any: Option<Vec<QuiltVersions>>,
+        all: Option<Vec<QuiltVersions>>,
+    },
+}
+
+impl From<QuiltVersions> for VersionRange {
+    fn from(versions: QuiltVersions) -> Self {
+        match versions {
+            QuiltVersions::Single(s) => VersionRange::parse(&s),
+            QuiltVersions::Any(any) | QuiltVersions::Object { any: Some(any), .. } => {
+                VersionRange::Any(any.into_iter().map(Into::into).collect())
+            }
+            QuiltVersions::Object { all, .. } => VersionRange::All(
+                all.unwrap_or_default()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuiltProvides {
+    Id(String),
+    Object { id: String },
+}
+
+#[derive(Deserialize)]
+struct FabricModJson {
+    id: String,
+    version: String,
+    #[serde(default)]
+    depends: BTreeMap<String, FabricVersions>,
+    #[serde(default)]
+    provides: Vec<String>,
+    #[serde(default)]
+    jars: Vec<FabricJar>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FabricVersions {
+    Single(String),
+    Any(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct FabricJar {
+    file: String,
+}
+
+/// Strips the maven group that Quilt allows in front of mod IDs
+fn strip_group(id: &str) -> String {
+    id.rsplit(':').next().unwrap_or(id).to_owned()
+}
+
+/// Reads the metadata of a mod jar, returning `None` if it is not a Quilt or Fabric mod
+fn read_mod<R: Read + Seek>(file: &Path, archive: &mut ZipArchive<R>) -> Result<Option<ModInfo>> {
+    let (mut info, jars) = if archive.index_for_name("quilt.mod.json").is_some() {
+        let json: QuiltModJson = serde_json::from_reader(archive.by_name("quilt.mod.json")?)?;
+        let loader = json.quilt_loader;
+        let depends = loader
+            .depends
+            .into_iter()
+            .filter_map(|d| match d {
+                QuiltDependency::Id(id) => Some(Dependency {
+                    id: strip_group(&id),
+                    versions: VersionRange::parse("*"),
+                    optional: false,
+                }),
+                QuiltDependency::Object {
+                    id,
+                    versions,
+                    optional,
+                } => Some(Dependency {
+                    id: strip_group(&id),
+                    versions: versions.map_or_else(|| VersionRange::parse("*"), Into::into),
+                    optional,
+                }),
+                QuiltDependency::Any(_) => None,
+            })
+            .collect();
+        let provides = loader
+            .provides
+            .into_iter()
+            .map(|p| match p {
+                QuiltProvides::Id(id) | QuiltProvides::Object { id } => strip_group(&id),
+            })
+            .collect();
+        let info = ModInfo {
+            id: loader.id,
+            version: loader.version,
+            file: file.to_owned(),
+            depends,
+            provides,
+        };
+        (info, loader.jars)
+    } else if archive.index_for_name("fabric.mod.json").is_some() {
+        let json: FabricModJson = serde_json::from_reader(archive.by_name("fabric.mod.json")?)?;
+        let depends = json
+            .depends
+            .into_iter()
+            .map(|(id, versions)| Dependency {
+                id,
+                versions: match versions {
+                    FabricVersions::Single(s) => VersionRange::parse(&s),
+                    FabricVersions::Any(any) => {
+                        VersionRange::Any(any.iter().map(|s| VersionRange::parse(s)).collect())
+                    }
+                },
+                optional: false,
+            })
+            .collect();
+        let info = ModInfo {
+            id: json.id,
+            version: json.version,
+            file: file.to_owned(),
+            depends,
+            provides: json.provides,
+        };
+        (info, json.jars.into_iter().map(|j| j.file).collect())
+    } else {
+        return Ok(None);
+    };
+
+    for jar in jars {
+        let mut bytes = Vec::new();
+        archive.by_name(&jar)?.read_to_end(&mut bytes)?;
+        if let Some(nested) = read_mod(&file.join(&jar), &mut ZipArchive::new(Cursor::new(bytes))?)?
+        {
+            info.provides.push(nested.id);
+            info.provides.extend(nested.provides);
+        }
+    }
+
+    Ok(Some(info))
+}
+
+#[derive(Debug, Default)]
+pub struct ModsFolder {
+    pub mods: Vec<ModInfo>,
+    /// Jars that could not be read, and why
+    pub unreadable: Vec<(PathBuf, String)>,
+}
+
+pub fn read_mods_dir(mods_dir: &Path) -> Result<ModsFolder> {
+    let mut folder = ModsFolder::default();
+
+    let mut paths = fs::read_dir(mods_dir)
+        .with_context(|| format!("Could not read mods folder {}", mods_dir.display()))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.sort();
+    for path in paths {
+        if path.extension().is_none_or(|e| e != "jar") {
+            continue;
+        }
+        let file = PathBuf::from(path.file_name().unwrap_or_default());
+        let result = ZipArchive::new(File::open(&path)?)
+            .map_err(Into::into)
+            .and_then(|mut archive| read_mod(&file, &mut archive));
+        match result {
+            Ok(Some(info)) => folder.mods.push(info),
+            Ok(None) => folder
+                .unreadable
+                .push((file, "not a Quilt or Fabric mod".into())),
+            Err(error) => folder.unreadable.push((file, error.to_string())),
+        }
+    }
+
+    Ok(folder)
+}
+
+#[derive(Debug, Clone)]
+pub struct Incompatibility {
+    pub file: PathBuf,
+    pub dependency: String,
+    pub versions: VersionRange,
+    pub selected: String,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requires {} {}, but {} is selected",
+            self.file.display(),
+            self.dependency,
+            self.versions,
+            self.selected
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MissingDependency {
+    pub file: PathBuf,
+    pub dependency: String,
+    pub versions: VersionRange,
+}
+
+impl fmt::Display for MissingDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requires {} {}, which is not installed",
+            self.file.display(),
+            self.dependency,
+            self.versions
+        )
+    }
+}
+
+/// A mod ID found in more than one jar
+#[derive(Debug, Clone)]
+pub struct Duplicate {
+    pub id: String,
+    /// The jars and the versions they contain
+    pub mods: Vec<(PathBuf, String)>,
+}
+
+impl fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mods = self
+            .mods
+            .iter()
+            .map(|(file, version)| format!("{} ({version})", file.display()))
+            .collect::<Vec<_>>();
+        write!(f, "{} in {}", self.id, mods.join(", "))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CompatibilityReport {
+    pub mods: usize,
+    pub incompatible: Vec<Incompatibility>,
+    pub duplicates: Vec<Duplicate>,
+    pub missing: Vec<MissingDependency>,
+    pub unreadable: Vec<(PathBuf, String)>,
+}
+
+impl CompatibilityReport {
+    pub fn has_problems(&self) -> bool {
+        !(self.incompatible.is_empty() && self.duplicates.is_empty() && self.missing.is_empty())
+    }
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Checked {} mods", self.mods)?;
+        if !self.has_problems() {
+            writeln!(f, "No problems found")?;
+        }
+        if !self.incompatible.is_empty() {
+            writeln!(f, "\nIncompatible mods:")?;
+            for incompatibility in &self.incompatible {
+                writeln!(f, "  {incompatibility}")?;
+            }
+        }
+        if !self.duplicates.is_empty() {
+            writeln!(f, "\nDuplicate mods:")?;
+            for duplicate in &self.duplicates {
+                writeln!(f, "  {duplicate}")?;
+            }
+        }
+        if !self.missing.is_empty() {
+            writeln!(f, "\nMissing dependencies:")?;
+            for missing in &self.missing {
+                writeln!(f, "  {missing}")?;
+            }
+        }
+        if !self.unreadable.is_empty() {
+            writeln!(f, "\nSkipped files:")?;
+            for (file, reason) in &self.unreadable {
+                writeln!(f, "  {}: {reason}", file.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks the mods in `mods_dir` against the selected versions and each other
+pub fn check_mods(
+    mods_dir: &Path,
+    minecraft_version: &MinecraftVersion,
+    loader_version: &LoaderVersion,
+) -> Result<CompatibilityReport> {
+    let ModsFolder { mods, unreadable } = read_mods_dir(mods_dir)?;
+    let mut report = CompatibilityReport {
+        mods: mods.len(),
+        unreadable,
+        ..Default::default()
+    };
+
+    let mut mods_by_id = BTreeMap::<&str, Vec<&ModInfo>>::new();
+    for info in &mods {
+        mods_by_id.entry(&info.id).or_default().push(info);
+    }
+    let available = BUILTIN_IDS
+        .iter()
+        .copied()
+        .chain(mods.iter().map(|m| m.id.as_str()))
+        .chain(
+            mods.iter()
+                .flat_map(|m| m.provides.iter().map(String::as_str)),
+        )
+        .collect::<HashSet<_>>();
+
+    let loader_version = loader_version.version.to_string();
+    for info in &mods {
+        for dependency in &info.depends {
+            let selected = match dependency.id.as_str() {
+                "minecraft" => &minecraft_version.version,
+                "quilt_loader" => &loader_version,
+                _ => {
+                    if !dependency.optional && !available.contains(dependency.id.as_str()) {
+                        report.missing.push(MissingDependency {
+                            file: info.file.clone(),
+                            dependency: dependency.id.clone(),
+                            versions: dependency.versions.clone(),
+                        });
+                    }
+                    continue;
+                }
+            };
+            if dependency.versions.matches(selected) == Some(false) {
+                report.incompatible.push(Incompatibility {
+                    file: info.file.clone(),
+                    dependency: dependency.id.clone(),
+                    versions: dependency.versions.clone(),
+                    selected: selected.clone(),
+                });
+            }
+        }
+    }
+
+    report.duplicates = mods_by_id
+        .into_iter()
+        .filter(|(_, mods)| mods.len() > 1)
+        .map(|(id, mods)| Duplicate {
+            id: id.to_owned(),
+            mods: mods
+                .into_iter()
+                .map(|m| (m.file.clone(), m.version.clone()))
+                .collect(),
+        })
+        .collect();
+
+    Ok(report)
+}
+
+/// Where a mod jar from a lockfile or config file goes in `mods_dir`
+pub fn mod_path(artifact: &LockedArtifact, mods_dir: &Path) -> Result<PathBuf> {
+    let file_name = Path::new(&artifact.name)
+        .file_name()
+        .with_context(|| format!("Invalid file name {}", artifact.name))?;
+    Ok(mods_dir.join(file_name))
+}
+
+/// Whether the mod jar is already in `mods_dir` with the expected hash
+pub fn is_mod_installed(artifact: &LockedArtifact, mods_dir: &Path) -> bool {
+    mod_path(artifact, mods_dir)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .is_some_and(|bytes| artifact.checksum.matches(&bytes))
+}
+
+/// Downloads a mod jar into `mods_dir`, replacing any file of the same name
+pub async fn install_mod(
+    client: &Client,
+    artifact: &LockedArtifact,
+    mods_dir: &Path,
+) -> Result<()> {
+    let path = mod_path(artifact, mods_dir)?;
+    let contents =
+        download::download_verified(client, &artifact.name, &artifact.url, &artifact.checksum)
+            .await?;
+    download::place(&path, &contents)?;
+    Ok(())
+}
+
+/// Picks the newest Quilt Loader version allowed by every mod in `mods_dir`,
+/// preferring versions in `channel` or more stable ones.
+/// `loader_versions` must be ordered newest first, like meta returns them.
+pub fn select_loader_version(
+    mods_dir: &Path,
+    loader_versions: Vec<LoaderVersion>,
+    channel: LoaderChannel,
+) -> Result<LoaderVersion> {
+    let mods = read_mods_dir(mods_dir)?.mods;
+    let requirements = mods
+        .iter()
+        .flat_map(|m| &m.depends)
+        .filter(|d| d.id == "quilt_loader")
+        .map(|d| &d.versions)
+        .collect::<Vec<_>>();
+    let minimum = requirements.iter().filter_map(|r| r.minimum()).max();
+    if let Some(minimum) = &minimum {
+        info!("The installed mods require Quilt Loader {minimum} or newer");
+    }
+
+    let satisfies = |v: &LoaderVersion| {
+        let version = v.version.to_string();
+        requirements
+            .iter()
+            .all(|r| r.matches(&version) != Some(false))
+    };
+    let preferred = loader_versions
+        .iter()
+        .find(|v| v.channel() >= channel && satisfies(v));
+    match preferred.or_else(|| loader_versions.iter().find(|v| satisfies(v))) {
+        Some(version) => Ok(version.clone()),
+        None => bail!(
+            "Could not find a Quilt Loader version satisfying the mods in {}",
+            mods_dir.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use semver::Version;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    /// A jar containing the given files
+    fn jar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn quilt_mod(id: &str, version: &str, depends: &str) -> Vec<u8> {
+        let json = format!(
+            r#"{{"quilt_loader": {{"id": "{id}", "version": "{version}", "depends": {depends}}}}}"#
+        );
+        jar(&[("quilt.mod.json", json.as_bytes())])
+    }
+
+    /// A mods folder in the temporary directory with the given jars
+    fn mods_dir(name: &str, jars: &[(&str, Vec<u8>)]) -> TempDir {
+        let dir = TempDir::new(&format!("mods-{name}"));
+        for (file, contents) in jars {
+            fs::write(dir.join(file), contents).unwrap();
+        }
+        dir
+    }
+
+    fn check(dir: &Path) -> CompatibilityReport {
+        let minecraft = MinecraftVersion::from_id("1.20.1");
+        let loader = LoaderVersion::from_version(Version::new(0, 21, 0));
+        check_mods(dir, &minecraft, &loader).unwrap()
+    }
+
+    #[test]
+    fn finds_duplicates() {
+        let dir = mods_dir(
+            "duplicates",
+            &[
+                ("a-1.jar", quilt_mod("a", "1.0.0", "[]")),
+                ("a-2.jar", quilt_mod("a", "2.0.0", "[]")),
+                ("b.jar", quilt_mod("b", "1.0.0", "[]")),
+            ],
+        );
+
+        let report = check(&dir);
+
+        assert_eq!(report.mods, 3);
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].id, "a");
+        assert_eq!(
+            report.duplicates[0].mods,
+            [
+                ("a-1.jar".into(), "1.0.0".into()),
+                ("a-2.jar".into(), "2.0.0".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_missing_dependencies() {
+        let fabric_json = br#"{"id": "c", "version": "1.0.0", "depends": {"fabric-api": "*"}}"#;
+        let dir = mods_dir(
+            "missing",
+            &[
+                (
+                    "a.jar",
+                    quilt_mod(
+                        "a",
+                        "1.0.0",
+                        r#"["b", {"id": "org.example:d", "optional": true}]"#,
+                    ),
+                ),
+                ("c.jar", jar(&[("fabric.mod.json", fabric_json)])),
+                ("readme.txt", b"not a jar".to_vec()),
+            ],
+        );
+
+        let report = check(&dir);
+
+        let missing = report
+            .missing
+            .iter()
+            .map(|m| (m.file.to_str().unwrap(), m.dependency.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(missing, [("a.jar", "b"), ("c.jar", "fabric-api")]);
+        assert!(report.unreadable.is_empty());
+    }
+
+    #[test]
+    fn nested_jars_provide_their_ids() {
+        let nested = quilt_mod("library", "1.0.0", "[]");
+        let outer_json = br#"{"quilt_loader": {
+            "id": "outer", "version": "1.0.0",
+            "provides": ["outer_api"],
+            "jars": ["META-INF/jars/library.jar"]
+        }}"#;
+        let outer = jar(&[
+            ("quilt.mod.json", outer_json),
+            ("META-INF/jars/library.jar", &nested),
+        ]);
+        let dir = mods_dir(
+            "nested",
+            &[
+                ("outer.jar", outer),
+                (
+                    "user.jar",
+                    quilt_mod("user", "1.0.0", r#"["library", "outer_api"]"#),
+                ),
+            ],
+        );
+
+        let report = check(&dir);
+
+        assert_eq!(report.mods, 2);
+        assert!(report.missing.is_empty());
+        assert!(report.duplicates.is_empty());
+    }
+
+    #[test]
+    fn finds_incompatible_versions() {
+        let dir = mods_dir(
+            "incompatible",
+            &[(
+                "a.jar",
+                quilt_mod(
+                    "a",
+                    "1.0.0",
+                    r#"[{"id": "minecraft", "versions": "1.19.x"},
+                        {"id": "quilt_loader", "versions": ">=0.20"}]"#,
+                ),
+            )],
+        );
+
+        let report = check(&dir);
+
+        assert_eq!(report.incompatible.len(), 1);
+        assert_eq!(report.incompatible[0].dependency, "minecraft");
+        assert_eq!(report.incompatible[0].selected, "1.20.1");
+    }
+
+    /// Loader versions newest first, like meta lists them
+    fn loader_versions(versions: &[&str]) -> Vec<LoaderVersion> {
+        versions
+            .iter()
+            .map(|v| LoaderVersion::from_version(v.parse().unwrap()))
+            .collect()
+    }
+
+    fn select(dir: &Path, channel: LoaderChannel) -> Result<String> {
+        let versions = loader_versions(&["0.22.0-beta.2", "0.21.2", "0.21.0", "0.20.0"]);
+        select_loader_version(dir, versions, channel).map(|v| v.to_string())
+    }
+
+    #[test]
+    fn selects_the_newest_loader_the_mods_allow() {
+        let dir = mods_dir(
+            "select-newest",
+            &[
+                (
+                    "a.jar",
+                    quilt_mod(
+                        "a",
+                        "1.0.0",
+                        r#"[{"id": "quilt_loader", "versions": ">=0.20"}]"#,
+                    ),
+                ),
+                (
+                    "b.jar",
+                    quilt_mod(
+                        "b",
+                        "1.0.0",
+                        r#"[{"id": "quilt_loader", "versions": "<0.21.1"}]"#,
+                    ),
+                ),
+            ],
+        );
+
+        assert_eq!(select(&dir, LoaderChannel::Stable).unwrap(), "0.21.0");
+    }
+
+    #[test]
+    fn prefers_the_channel_and_falls_back_to_less_stable_loaders() {
+        let dir = mods_dir(
+            "select-channel",
+            &[("a.jar", quilt_mod("a", "1.0.0", "[]"))],
+        );
+
+        assert_eq!(select(&dir, LoaderChannel::Stable).unwrap(), "0.21.2");
+        assert_eq!(select(&dir, LoaderChannel::Beta).unwrap(), "0.22.0-beta.2");
+
+        let dir = mods_dir(
+            "select-fallback",
+            &[(
+                "a.jar",
+                quilt_mod(
+                    "a",
+                    "1.0.0",
+                    r#"[{"id": "quilt_loader", "versions": ">=0.22.0-"}]"#,
+                ),
+            )],
+        );
+        assert_eq!(
+            select(&dir, LoaderChannel::Stable).unwrap(),
+            "0.22.0-beta.2"
+        );
+    }
+
+    #[test]
+    fn fails_when_no_loader_satisfies_the_mods() {
+        let dir = mods_dir(
+            "select-none",
+            &[(
+                "a.jar",
+                quilt_mod(
+                    "a",
+                    "1.0.0",
+                    r#"[{"id": "quilt_loader", "versions": ">=0.23"}]"#,
+                ),
+            )],
+        );
+
+        assert!(select(&dir, LoaderChannel::Stable).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Utc};
+use derive_more::Display;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+pub const VERSION_MANIFEST_URL: &str =
+    "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VersionManifest {
+    pub versions: Vec<ManifestVersion>,
+}
+
+/// A vanilla version as listed in Mojang's version manifest
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestVersion {
+    pub id: String,
+    /// The type in the manifest, which lumps pre-releases and April Fools versions with snapshots
+    #[serde(rename = "type")]
+    pub manifest_type: ReleaseType,
+    pub release_time: DateTime<Utc>,
+    /// Where the version's launch JSON can be downloaded
+    pub url: String,
+    pub sha1: String,
+}
+
+/// What kind of release a Minecraft version is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReleaseType {
+    #[default]
+    #[display(fmt = "release")]
+    Release,
+    #[display(fmt = "snapshot")]
+    Snapshot,
+    #[display(fmt = "pre-release")]
+    PreRelease,
+    #[display(fmt = "release-candidate")]
+    ReleaseCandidate,
+    #[display(fmt = "april-fools")]
+    AprilFools,
+    #[display(fmt = "old-beta")]
+    #[serde(alias = "old_beta")]
+    OldBeta,
+    #[display(fmt = "old-alpha")]
+    #[serde(alias = "old_alpha")]
+    OldAlpha,
+}
+
+impl ReleaseType {
+    pub const ALL: [Self; 7] = [
+        Self::Release,
+        Self::Snapshot,
+        Self::PreRelease,
+        Self::ReleaseCandidate,
+        Self::AprilFools,
+        Self::OldBeta,
+        Self::OldAlpha,
+    ];
+}
+
+impl std::str::FromStr for ReleaseType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.to_string() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|t| t.to_string());
+                format!("expected one of {}", names.join(", "))
+            })
+    }
+}
+
+impl ManifestVersion {
+    /// The release type, telling pre-releases, release candidates and April Fools versions
+    /// apart from the snapshots they are listed as
+    pub fn release_type(&self) -> ReleaseType {
+        if self.manifest_type != ReleaseType::Snapshot {
+            return self.manifest_type;
+        }
+        let id = self.id.to_lowercase();
+        if id.contains("-rc") || id.contains(" release candidate") {
+            ReleaseType::ReleaseCandidate
+        } else if id.contains("-pre") || id.contains(" pre-release") {
+            ReleaseType::PreRelease
+        } else if self.release_time.month() == 4 && self.release_time.day() == 1 {
+            // Joke versions come out on April 1st, under names unlike regular snapshots
+            ReleaseType::AprilFools
+        } else {
+            ReleaseType::Snapshot
+        }
+    }
+}
+
+/// The part of a launch JSON telling which Java version the game needs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaRequirement {
+    /// Missing for versions older than the launcher's bundled Java runtimes, which need Java 8
+    pub java_version: Option<JavaVersion>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaVersion {
+    pub major_version: u32,
+}
+
+impl JavaRequirement {
+    pub fn major_version(&self) -> u32 {
+        self.java_version.as_ref().map_or(8, |v| v.major_version)
+    }
+}
+
+pub async fn fetch_version_manifest(client: &Client, url: &str) -> Result<VersionManifest> {
+    Ok(client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
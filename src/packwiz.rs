@@ -0,0 +1,439 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use crate::download;
+use crate::hash::{Checksum, HashFormat};
+use crate::installer::{self, ClientInstallation, Installation};
+
+/// Where a file of a pack is read from
+#[derive(Debug, Clone, derive_more::Display)]
+pub enum PackLocation {
+    #[display(fmt = "{}", "_0.display()")]
+    Local(PathBuf),
+    Remote(Url),
+}
+
+impl PackLocation {
+    pub fn parse(location: &str) -> Result<Self> {
+        if location.starts_with("http://") || location.starts_with("https://") {
+            Ok(Self::Remote(Url::parse(location)?))
+        } else {
+            Ok(Self::Local(location.into()))
+        }
+    }
+
+    /// Resolves `path` relative to the directory containing this file
+    fn join(&self, path: &str) -> Result<Self> {
+        Ok(match self {
+            Self::Local(file) => Self::Local(
+                file.parent()
+                    .unwrap_or(Path::new(""))
+                    .join(safe_relative_path(path)?),
+            ),
+            Self::Remote(url) => Self::Remote(url.join(path)?),
+        })
+    }
+
+    async fn read(&self, client: &Client) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Local(path) => {
+                fs::read(path).with_context(|| format!("Could not read {}", path.display()))?
+            }
+            Self::Remote(url) => client
+                .get(url.clone())
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec(),
+        })
+    }
+
+    async fn read_verified(&self, client: &Client, checksum: &Checksum) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Local(path) => {
+                let contents =
+                    fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+                checksum.verify(&self.to_string(), &contents)?;
+                contents
+            }
+            Self::Remote(url) => {
+                download::download_verified(client, &self.to_string(), url.as_str(), checksum)
+                    .await?
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Pack {
+    name: String,
+    index: IndexRef,
+    versions: PackVersions,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct IndexRef {
+    file: String,
+    hash_format: HashFormat,
+    hash: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PackVersions {
+    minecraft: String,
+    quilt: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Index {
+    hash_format: HashFormat,
+    #[serde(default)]
+    files: Vec<IndexFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct IndexFile {
+    file: String,
+    hash: String,
+    hash_format: Option<HashFormat>,
+    alias: Option<String>,
+    #[serde(default)]
+    metafile: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct MetaFile {
+    name: String,
+    filename: String,
+    #[serde(default)]
+    side: Side,
+    download: MetaDownload,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct MetaDownload {
+    url: Option<String>,
+    hash_format: HashFormat,
+    hash: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Side {
+    Client,
+    Server,
+    #[default]
+    Both,
+}
+
+impl Side {
+    fn includes(self, installation: Installation) -> bool {
+        match self {
+            Side::Both => true,
+            Side::Client => installation == Installation::Client,
+            Side::Server => installation == Installation::Server,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PackInstallation {
+    pub pack: PackLocation,
+    pub side: Installation,
+    pub install_dir: PathBuf,
+    pub generate_profile: bool,
+}
+
+/// The files of a pack, split by whether they were installed
+#[derive(Debug, Default)]
+pub struct PackReport {
+    pub installed: Vec<PathBuf>,
+    /// Files for the other side of the installation
+    pub skipped: Vec<PathBuf>,
+    /// Files without a download URL, e.g. CurseForge mods that must be fetched manually
+    pub pending: Vec<String>,
+}
+
+/// Installs a packwiz pack on top of a Quilt client installation, or the server side files
+/// of a pack into a server directory where Quilt Loader is set up separately
+pub async fn install_pack(client: Client, args: PackInstallation) -> Result<PackReport> {
+    let pack: Pack = toml::from_str(&String::from_utf8(args.pack.read(&client).await?)?)
+        .with_context(|| format!("{} is not a valid pack.toml", args.pack))?;
+    info!(
+        "Installing pack {} for Minecraft {}",
+        pack.name, pack.versions.minecraft
+    );
+
+    let Some(quilt_version) = &pack.versions.quilt else {
+        bail!("The pack does not use Quilt Loader");
+    };
+    match args.side {
+        Installation::Client => {
+            let (minecraft_version, loader_version) =
+                installer::find_versions(client.clone(), &pack.versions.minecraft, quilt_version)
+                    .await?;
+            installer::install_client(
+                client.clone(),
+                ClientInstallation {
+                    minecraft_version,
+                    loader_version,
+                    install_dir: args.install_dir.clone(),
+                    generate_profile: args.generate_profile,
+                    download_libraries: false,
+                    disabled_fixups: Vec::new(),
+                    pinned_artifacts: Vec::new(),
+                },
+            )
+            .await?;
+        }
+        // The installer can't set up servers yet, so only the pack's files are installed
+        Installation::Server => warn!(
+            "Not installing the server itself, set up Minecraft {} with Quilt Loader {quilt_version} separately",
+            pack.versions.minecraft
+        ),
+    }
+
+    let index_location = args.pack.join(&pack.index.file)?;
+    let index = index_location
+        .read_verified(
+            &client,
+            &Checksum::new(pack.index.hash_format, &pack.index.hash),
+        )
+        .await?;
+    let index: Index = toml::from_str(&String::from_utf8(index)?)
+        .with_context(|| format!("{} is not a valid index.toml", index_location))?;
+
+    let mut report = PackReport::default();
+    for file in index.files {
+        let location = index_location.join(&file.file)?;
+        let checksum = Checksum::new(file.hash_format.unwrap_or(index.hash_format), &file.hash);
+        let contents = location.read_verified(&client, &checksum).await?;
+
+        let index_path = safe_relative_path(file.alias.as_ref().unwrap_or(&file.file))?;
+        if !file.metafile {
+            download::place(&args.install_dir.join(&index_path), &contents)?;
+            report.installed.push(index_path);
+            continue;
+        }
+
+        let meta: MetaFile = toml::from_str(&String::from_utf8(contents)?)
+            .with_context(|| format!("{} is not a valid metadata file", file.file))?;
+        let path = index_path
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(safe_relative_path(&meta.filename)?);
+        if !meta.side.includes(args.side) {
+            report.skipped.push(path);
+            continue;
+        }
+        let Some(url) = &meta.download.url else {
+            report.pending.push(meta.name);
+            continue;
+        };
+
+        let contents = download::download_verified(
+            &client,
+            &meta.filename,
+            url,
+            &Checksum::new(meta.download.hash_format, &meta.download.hash),
+        )
+        .await?;
+        download::place(&args.install_dir.join(&path), &contents)?;
+        report.installed.push(path);
+    }
+
+    Ok(report)
+}
+
+/// Rejects paths that would escape the installation directory
+fn safe_relative_path(path: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(path);
+    if !path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        bail!("Refusing to install to {}", path.display());
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashMismatch;
+    use crate::temp_dir::TempDir;
+
+    fn sha256(contents: &str) -> String {
+        Checksum::of(HashFormat::Sha256, contents.as_bytes()).value
+    }
+
+    /// Writes a pack with the given files to `dir`, returning the path of its pack.toml
+    fn write_pack(dir: &Path, files: &[(&str, &str, bool)]) -> PathBuf {
+        let mut index = String::from("hash-format = \"sha256\"\n");
+        for (path, contents, metafile) in files {
+            let file = dir.join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(&file, contents).unwrap();
+            index += &format!(
+                "[[files]]\nfile = \"{path}\"\nhash = \"{}\"\nmetafile = {metafile}\n",
+                sha256(contents)
+            );
+        }
+        write_index(dir, &index)
+    }
+
+    /// Writes the index of a pack and a pack.toml pointing at it, returning the latter's path
+    fn write_index(dir: &Path, index: &str) -> PathBuf {
+        fs::write(dir.join("index.toml"), index).unwrap();
+        let pack = format!(
+            "name = \"Test Pack\"\n\
+             [index]\nfile = \"index.toml\"\nhash-format = \"sha256\"\nhash = \"{}\"\n\
+             [versions]\nminecraft = \"1.20.1\"\nquilt = \"0.21.0\"\n",
+            sha256(index)
+        );
+        fs::write(dir.join("pack.toml"), pack).unwrap();
+        dir.join("pack.toml")
+    }
+
+    fn metafile(name: &str, filename: &str, side: &str, url: Option<&str>) -> String {
+        let url = url.map_or_else(String::new, |url| format!("url = \"{url}\"\n"));
+        format!(
+            "name = \"{name}\"\nfilename = \"{filename}\"\nside = \"{side}\"\n\
+             [download]\n{url}hash-format = \"sha1\"\nhash = \"00\"\n"
+        )
+    }
+
+    fn install_server_side(pack: &Path, install_dir: &Path) -> Result<PackReport> {
+        let args = PackInstallation {
+            pack: PackLocation::Local(pack.to_owned()),
+            side: Installation::Server,
+            install_dir: install_dir.to_owned(),
+            generate_profile: false,
+        };
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(install_pack(Client::new(), args))
+    }
+
+    #[test]
+    fn sides_include_their_installations() {
+        assert!(Side::Both.includes(Installation::Client));
+        assert!(Side::Both.includes(Installation::Server));
+        assert!(Side::Client.includes(Installation::Client));
+        assert!(!Side::Client.includes(Installation::Server));
+        assert!(!Side::Server.includes(Installation::Client));
+    }
+
+    #[test]
+    fn paths_must_stay_inside_the_installation() {
+        assert_eq!(
+            safe_relative_path("config/mod.toml").unwrap(),
+            Path::new("config/mod.toml")
+        );
+        assert!(safe_relative_path("./options.txt").is_ok());
+        assert!(safe_relative_path("../options.txt").is_err());
+        assert!(safe_relative_path("config/../../options.txt").is_err());
+        assert!(safe_relative_path("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn installs_the_server_side_files() {
+        let pack_dir = TempDir::new("packwiz-pack");
+        let install_dir = TempDir::new("packwiz-install");
+        let client_mod = metafile(
+            "Client Mod",
+            "client.jar",
+            "client",
+            Some("https://example.com"),
+        );
+        let server_mod = metafile("Server Mod", "server.jar", "server", None);
+        let pack = write_pack(
+            &pack_dir,
+            &[
+                ("config/server.properties", "motd=Test", false),
+                ("mods/client.pw.toml", &client_mod, true),
+                ("mods/server.pw.toml", &server_mod, true),
+            ],
+        );
+
+        let report = install_server_side(&pack, &install_dir).unwrap();
+
+        assert_eq!(
+            report.installed,
+            [PathBuf::from("config/server.properties")]
+        );
+        assert_eq!(
+            fs::read_to_string(install_dir.join("config/server.properties")).unwrap(),
+            "motd=Test"
+        );
+        // Metafiles are resolved relative to their folder, under the name they point at
+        assert_eq!(report.skipped, [PathBuf::from("mods/client.jar")]);
+        assert_eq!(report.pending, ["Server Mod"]);
+        assert!(!install_dir.join("mods").exists());
+    }
+
+    #[test]
+    fn installs_files_under_their_alias() {
+        let pack_dir = TempDir::new("packwiz-pack");
+        let install_dir = TempDir::new("packwiz-install");
+        write_pack(&pack_dir, &[("defaults/options.txt", "fov:90", false)]);
+        let index = fs::read_to_string(pack_dir.join("index.toml"))
+            .unwrap()
+            .replace(
+                "metafile = false",
+                "metafile = false\nalias = \"options.txt\"",
+            );
+        let pack = write_index(&pack_dir, &index);
+
+        let report = install_server_side(&pack, &install_dir).unwrap();
+
+        assert_eq!(report.installed, [PathBuf::from("options.txt")]);
+        assert_eq!(
+            fs::read_to_string(install_dir.join("options.txt")).unwrap(),
+            "fov:90"
+        );
+    }
+
+    #[test]
+    fn rejects_files_that_do_not_match_the_index() {
+        let pack_dir = TempDir::new("packwiz-pack");
+        let install_dir = TempDir::new("packwiz-install");
+        let pack = write_pack(&pack_dir, &[("options.txt", "fov:90", false)]);
+        fs::write(pack_dir.join("options.txt"), "fov:110").unwrap();
+
+        let error = install_server_side(&pack, &install_dir).unwrap_err();
+
+        assert!(error.downcast_ref::<HashMismatch>().is_some(), "{error:#}");
+        assert!(!install_dir.join("options.txt").exists());
+    }
+
+    #[test]
+    fn rejects_index_entries_outside_the_pack() {
+        let pack_dir = TempDir::new("packwiz-pack");
+        let install_dir = TempDir::new("packwiz-install");
+        write_pack(&pack_dir, &[("options.txt", "fov:90", false)]);
+        let index = fs::read_to_string(pack_dir.join("index.toml"))
+            .unwrap()
+            .replace("file = \"options.txt\"", "file = \"../options.txt\"");
+        let pack = write_index(&pack_dir, &index);
+
+        let error = install_server_side(&pack, &install_dir).unwrap_err();
+
+        assert!(error.to_string().starts_with("Refusing"), "{error:#}");
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::InstallError;
+use crate::hash::{Checksum, HashFormat};
+
+/// A launch profile, i.e. the version JSON the launcher reads from `versions/<id>/<id>.json`
+///
+/// Fields we don't use are kept in `other`, so a profile survives a round trip unchanged.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchProfile {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherits_from: Option<String>,
+    pub main_class: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Arguments>,
+    #[serde(default)]
+    pub libraries: Vec<Library>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Arguments {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jvm: Option<Vec<Value>>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// A library entry of a launch profile
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Library {
+    /// Maven coordinates of the library
+    pub name: String,
+    /// The maven repository the library is downloaded from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<String>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl LaunchProfile {
+    /// Parses a launch profile, naming `origin` in the error if it is malformed
+    pub fn parse(json: &str, origin: &str) -> Result<Self, InstallError> {
+        serde_json::from_str(json).map_err(|error| InstallError::CorruptProfile {
+            origin: origin.into(),
+            reason: error.to_string(),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("launch profiles always serialize")
+    }
+}
+
+impl Library {
+    /// The strongest hash embedded in the profile, if any
+    pub fn checksum(&self) -> Option<Checksum> {
+        [
+            (HashFormat::Sha512, &self.sha512),
+            (HashFormat::Sha256, &self.sha256),
+            (HashFormat::Sha1, &self.sha1),
+        ]
+        .into_iter()
+        .find_map(|(format, value)| Some(Checksum::new(format, value.as_deref()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trips(json: &str) {
+        let profile = LaunchProfile::parse(json, "fixture").unwrap();
+
+        let expected: Value = serde_json::from_str(json).unwrap();
+        let actual: Value = serde_json::from_str(&profile.to_json()).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn profile_0_17_6_round_trips() {
+        assert_round_trips(include_str!("../tests/fixtures/profile-0.17.6-1.19.2.json"));
+    }
+
+    #[test]
+    fn profile_0_17_7_round_trips() {
+        assert_round_trips(include_str!("../tests/fixtures/profile-0.17.7-1.19.2.json"));
+    }
+
+    #[test]
+    fn empty_arguments_are_kept() {
+        let profile = LaunchProfile::parse(
+            r#"{"id": "a", "mainClass": "b", "arguments": {"game": [], "jvm": []}}"#,
+            "test",
+        )
+        .unwrap();
+
+        let json: Value = serde_json::from_str(&profile.to_json()).unwrap();
+        assert_eq!(json["arguments"]["game"], Value::Array(Vec::new()));
+        assert_eq!(json["arguments"]["jvm"], Value::Array(Vec::new()));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::version_range::{FlexVersion, VersionRange};
+
+/// How many versions are suggested at most
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The versions to suggest when nothing matches `query`: likely typos of a plain version,
+/// or the versions nearest to the bound of a requirement like `>=0.99`
+pub fn suggest_versions<'a>(
+    query: &str,
+    versions: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    if FlexVersion::parse(query).is_some() {
+        return did_you_mean(query, versions);
+    }
+    match VersionRange::parse(query).bound() {
+        Some(bound) => nearest(&bound, versions),
+        None => did_you_mean(query, versions),
+    }
+}
+
+/// The versions closest to `bound` in version order, closest first
+pub fn nearest<'a>(
+    bound: &FlexVersion,
+    versions: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let mut ordered = versions
+        .into_iter()
+        .filter_map(|v| FlexVersion::parse(v).map(|parsed| (parsed, v)))
+        .collect::<Vec<_>>();
+    ordered.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let position = ordered.partition_point(|(v, _)| v < bound);
+    let (below, above) = ordered.split_at(position);
+
+    let mut below = below.iter().rev();
+    let mut above = above.iter();
+    let mut suggestions = Vec::new();
+    while suggestions.len() < MAX_SUGGESTIONS {
+        let next = [above.next(), below.next()];
+        if next.iter().all(Option::is_none) {
+            break;
+        }
+        suggestions.extend(next.into_iter().flatten().map(|(_, v)| (*v).to_owned()));
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// The versions `query` is most likely a typo of, best first
+///
+/// Candidates are ranked by edit distance, ties going to the versions
+/// closest to `query` in version order.
+pub fn did_you_mean<'a>(query: &str, versions: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let versions = versions.into_iter().collect::<Vec<_>>();
+    let max_distance = (query.chars().count() / 3).max(2);
+
+    let mut ordered = versions
+        .iter()
+        .filter_map(|v| FlexVersion::parse(v).map(|parsed| (parsed, *v)))
+        .collect::<Vec<_>>();
+    ordered.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let query_position =
+        FlexVersion::parse(query).map(|query| ordered.partition_point(|(v, _)| *v < query));
+    let order_distance = |version: &str| {
+        let position = ordered.iter().position(|(_, v)| *v == version);
+        match (query_position, position) {
+            // The query sits between the versions at `query - 1` and `query`
+            (Some(query), Some(version)) if version < query => query - 1 - version,
+            (Some(query), Some(version)) => version - query,
+            _ => usize::MAX,
+        }
+    };
+
+    let mut suggestions = versions
+        .into_iter()
+        .filter(|v| *v != query)
+        .map(|v| (strsim::levenshtein(query, v), order_distance(v), v))
+        .filter(|(distance, _, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    suggestions.sort();
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, v)| v.to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSIONS: &[&str] = &["0.17.6", "0.17.7", "0.18.1", "0.19.0-beta.1", "0.19.2"];
+
+    #[test]
+    fn suggests_typos_of_plain_versions() {
+        assert_eq!(
+            suggest_versions("0.17.8", VERSIONS.iter().copied()),
+            ["0.17.7", "0.17.6", "0.18.1"]
+        );
+    }
+
+    #[test]
+    fn suggests_versions_around_a_requirement() {
+        assert_eq!(
+            suggest_versions(">=0.99", VERSIONS.iter().copied()),
+            ["0.19.2", "0.19.0-beta.1", "0.18.1"]
+        );
+        assert_eq!(
+            suggest_versions("~0.18.0", VERSIONS.iter().copied()),
+            ["0.18.1", "0.17.7", "0.19.0-beta.1"]
+        );
+    }
+}
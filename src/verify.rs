@@ -0,0 +1,295 @@
+use std::{fmt, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use log::debug;
+use reqwest::Client;
+use semver::Version;
+
+use crate::download;
+use crate::error::InstallError;
+use crate::hash::{Checksum, HashFormat};
+use crate::installer::{
+    self, ClientInstallation, LauncherProfiles, LoaderVersion, MinecraftVersion,
+};
+use crate::maven::{self, LibraryState, ResolvedLibrary};
+use crate::mojang::{self, VersionManifest};
+use crate::profile::LaunchProfile;
+
+#[derive(Debug, Clone)]
+pub enum Problem {
+    /// The Quilt version JSON is missing or could not be read, and why
+    CorruptVersion(String, String),
+    /// The vanilla version a Quilt version inherits from is not installed
+    MissingParent(String, String),
+    MissingLibrary(String, ResolvedLibrary),
+    CorruptLibrary(String, ResolvedLibrary),
+    /// The libraries could not be resolved to check them, and why
+    UnverifiableLibraries(String, String),
+    /// A launcher profile points at a Quilt version that is not installed
+    DanglingProfile(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CorruptVersion(version, error) => {
+                write!(f, "{version}: the version JSON is unreadable ({error})")
+            }
+            Self::MissingParent(version, parent) => {
+                write!(f, "{version}: Minecraft {parent} is not installed")
+            }
+            Self::MissingLibrary(version, library) => {
+                write!(f, "{version}: library {} is missing", library.artifact)
+            }
+            Self::CorruptLibrary(version, library) => {
+                write!(f, "{version}: library {} is corrupt", library.artifact)
+            }
+            Self::UnverifiableLibraries(version, error) => {
+                write!(f, "{version}: the libraries could not be checked ({error})")
+            }
+            Self::DanglingProfile(profile) => {
+                write!(f, "profile {profile} points at a missing version")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub problem: Problem,
+    pub repaired: bool,
+    /// Why repairing failed, if it was attempted and did
+    pub repair_error: Option<String>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.problem)?;
+        if self.repaired {
+            write!(f, " (repaired)")?;
+        }
+        if let Some(error) = &self.repair_error {
+            write!(f, " (repair failed: {error})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the Quilt versions installed in a client installation directory,
+/// repairing what can be repaired if `repair` is set
+pub async fn verify_installation(
+    client: Client,
+    install_dir: &Path,
+    repair: bool,
+) -> Result<Vec<Finding>> {
+    let versions_dir = install_dir.join("versions");
+    let libraries_dir = install_dir.join("libraries");
+
+    let mut problems = Vec::new();
+    let mut versions = fs::read_dir(&versions_dir)
+        .with_context(|| format!("Could not read {}", versions_dir.display()))?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>>>()?;
+    versions.retain(|v| v.starts_with("quilt-loader-"));
+    versions.sort();
+
+    for version in versions {
+        debug!("Verifying {version}");
+        let path = versions_dir.join(&version).join(format!("{version}.json"));
+        let profile = match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(LaunchProfile::parse(&json, &version)?))
+        {
+            Ok(profile) => profile,
+            Err(error) => {
+                problems.push(Problem::CorruptVersion(version, error.to_string()));
+                continue;
+            }
+        };
+
+        if let Some(parent) = &profile.inherits_from {
+            if !versions_dir
+                .join(parent)
+                .join(format!("{parent}.json"))
+                .exists()
+            {
+                problems.push(Problem::MissingParent(version.clone(), parent.clone()));
+            }
+        }
+
+        let libraries = match maven::resolve_libraries(&client, &profile).await {
+            Ok(libraries) => libraries,
+            Err(error) => {
+                problems.push(Problem::UnverifiableLibraries(version, error.to_string()));
+                continue;
+            }
+        };
+        for library in libraries {
+            match library.state(&libraries_dir) {
+                LibraryState::Present => {}
+                LibraryState::Missing => {
+                    problems.push(Problem::MissingLibrary(version.clone(), library))
+                }
+                LibraryState::Corrupt => {
+                    problems.push(Problem::CorruptLibrary(version.clone(), library))
+                }
+            }
+        }
+    }
+
+    let mut launcher_profiles = if install_dir.join("launcher_profiles.json").exists() {
+        Some(LauncherProfiles::read(install_dir)?)
+    } else {
+        None
+    };
+    if let Some(launcher_profiles) = &launcher_profiles {
+        problems.extend(
+            launcher_profiles
+                .dangling_profiles(install_dir)
+                .into_iter()
+                .map(Problem::DanglingProfile),
+        );
+    }
+
+    let mut findings = Vec::new();
+    let mut manifest = None;
+    for problem in problems {
+        let result = if repair {
+            repair_problem(
+                &client,
+                &problem,
+                install_dir,
+                &mut manifest,
+                &mut launcher_profiles,
+            )
+            .await
+        } else {
+            Ok(false)
+        };
+        let finding = match result {
+            Ok(repaired) => Finding {
+                problem,
+                repaired,
+                repair_error: None,
+            },
+            Err(error) => Finding {
+                problem,
+                repaired: false,
+                repair_error: Some(format!("{error:#}")),
+            },
+        };
+        findings.push(finding);
+    }
+
+    if let Some(launcher_profiles) = launcher_profiles {
+        if findings
+            .iter()
+            .any(|f| f.repaired && matches!(f.problem, Problem::DanglingProfile(_)))
+        {
+            launcher_profiles.write(install_dir)?;
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Repairs a problem if it can be, returning whether it was
+async fn repair_problem(
+    client: &Client,
+    problem: &Problem,
+    install_dir: &Path,
+    manifest: &mut Option<VersionManifest>,
+    launcher_profiles: &mut Option<LauncherProfiles>,
+) -> Result<bool> {
+    let versions_dir = install_dir.join("versions");
+    match problem {
+        Problem::CorruptVersion(version, _) => {
+            redownload_version(client, install_dir, version).await?;
+            Ok(true)
+        }
+        Problem::MissingParent(_, parent) => {
+            install_vanilla_version(client, manifest, &versions_dir, parent).await?;
+            Ok(true)
+        }
+        Problem::MissingLibrary(_, library) | Problem::CorruptLibrary(_, library) => {
+            library
+                .download(client, &install_dir.join("libraries"))
+                .await?;
+            Ok(true)
+        }
+        Problem::UnverifiableLibraries(..) => Ok(false),
+        Problem::DanglingProfile(profile) => match launcher_profiles {
+            Some(launcher_profiles) => {
+                launcher_profiles.remove_profile(profile);
+                Ok(true)
+            }
+            None => Ok(false),
+        },
+    }
+}
+
+/// Downloads a Quilt version JSON again, with the versions taken from its directory name
+/// `quilt-loader-<loader>-<minecraft>`. Both may contain dashes, so every split where the
+/// loader part is a valid version is tried until meta knows the pair.
+async fn redownload_version(client: &Client, install_dir: &Path, name: &str) -> Result<()> {
+    let versions = name.strip_prefix("quilt-loader-").unwrap_or(name);
+    for (i, _) in versions.match_indices('-') {
+        let (loader, minecraft) = (&versions[..i], &versions[i + 1..]);
+        let Ok(loader) = loader.parse::<Version>() else {
+            continue;
+        };
+        let installation = ClientInstallation {
+            minecraft_version: MinecraftVersion::from_id(minecraft),
+            loader_version: LoaderVersion::from_version(loader),
+            install_dir: install_dir.to_owned(),
+            generate_profile: false,
+            download_libraries: false,
+            disabled_fixups: Vec::new(),
+            pinned_artifacts: Vec::new(),
+        };
+        if installation.profile_name() != name {
+            continue;
+        }
+        let response = match installer::fetch_launch_profile(client, &installation).await {
+            Ok((_, response)) => response,
+            Err(InstallError::UnsupportedVersion { .. }) => continue,
+            Err(error) => return Err(error.into()),
+        };
+        let launch_profile = installer::fixed_launch_profile(&installation, &response)?;
+        let path = install_dir
+            .join("versions")
+            .join(name)
+            .join(format!("{name}.json"));
+        download::place(&path, launch_profile.to_json().as_bytes())?;
+        return Ok(());
+    }
+    bail!("Quilt meta has no launch profile matching {name}")
+}
+
+/// Downloads the launch JSON of a vanilla version, leaving the rest to the launcher
+async fn install_vanilla_version(
+    client: &Client,
+    manifest: &mut Option<VersionManifest>,
+    versions_dir: &Path,
+    id: &str,
+) -> Result<()> {
+    if manifest.is_none() {
+        *manifest =
+            Some(mojang::fetch_version_manifest(client, mojang::VERSION_MANIFEST_URL).await?);
+    }
+    let version = manifest
+        .iter()
+        .flat_map(|m| &m.versions)
+        .find(|v| v.id == id)
+        .with_context(|| format!("Could not find Minecraft {id} in Mojang's version manifest"))?;
+
+    let bytes = download::download_verified(
+        client,
+        &format!("{id}.json"),
+        &version.url,
+        &Checksum::new(HashFormat::Sha1, &version.sha1),
+    )
+    .await?;
+    download::place(&versions_dir.join(id).join(format!("{id}.json")), &bytes)?;
+    Ok(())
+}
@@ -0,0 +1,392 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A loosely parsed version, e.g. `1.20.1`, `0.19.0-beta.3` or `1.20`
+///
+/// Missing components count as zero, so `1.20` and `1.20.0` are equal.
+#[derive(Debug, Clone)]
+pub struct FlexVersion {
+    numbers: Vec<u64>,
+    pre: Option<String>,
+}
+
+impl FlexVersion {
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.split('+').next()?;
+        let (numbers, pre) = match s.split_once('-') {
+            Some((numbers, pre)) => (numbers, Some(pre.to_owned())),
+            None => (s, None),
+        };
+        Some(Self {
+            numbers: numbers
+                .split('.')
+                .map(|n| n.parse().ok())
+                .collect::<Option<_>>()?,
+            pre,
+        })
+    }
+
+    fn number(&self, i: usize) -> u64 {
+        self.numbers.get(i).copied().unwrap_or(0)
+    }
+
+    /// The smallest version above every version starting with the first `len` components
+    fn bump(&self, len: usize) -> Self {
+        let mut numbers = self.numbers.clone();
+        numbers.resize(len.max(1), 0);
+        *numbers.last_mut().unwrap() += 1;
+        Self { numbers, pre: None }
+    }
+}
+
+impl PartialEq for FlexVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FlexVersion {}
+
+impl PartialOrd for FlexVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FlexVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+        (0..len)
+            .map(|i| self.number(i).cmp(&other.number(i)))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_pre_release(a, b),
+            })
+    }
+}
+
+impl fmt::Display for FlexVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numbers = self.numbers.iter().map(u64::to_string).collect::<Vec<_>>();
+        write!(f, "{}", numbers.join("."))?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares dot separated pre-release identifiers like semver does
+fn compare_pre_release(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        return match (a.next(), b.next()) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => a.cmp(b),
+                };
+                if ordering.is_eq() {
+                    continue;
+                }
+                ordering
+            }
+        };
+    }
+}
+
+/// A single comparison such as `>=1.20`, `~0.19.2` or `1.20.x`
+#[derive(Debug, Clone)]
+enum Predicate {
+    Any,
+    Eq(FlexVersion),
+    Gt(FlexVersion),
+    Ge(FlexVersion),
+    Lt(FlexVersion),
+    Le(FlexVersion),
+    /// Inclusive minimum, exclusive maximum
+    Range(FlexVersion, FlexVersion),
+    /// A predicate over versions that cannot be compared, e.g. snapshots
+    Unknown,
+}
+
+impl Predicate {
+    fn parse(s: &str) -> Self {
+        if s == "*" {
+            return Self::Any;
+        }
+        let (operator, version) = match s.find(|c: char| c.is_ascii_alphanumeric()) {
+            Some(i) => s.split_at(i),
+            None => return Self::Unknown,
+        };
+
+        // Wildcards like `1.20.x` cover every version starting with the components before them
+        let wildcard = version
+            .split('.')
+            .position(|c| matches!(c, "x" | "X" | "*"));
+        let version = match wildcard {
+            Some(len) => version.split('.').take(len).collect::<Vec<_>>().join("."),
+            None => version.to_owned(),
+        };
+        if version.is_empty() {
+            return Self::Any;
+        }
+        let Some(version) = FlexVersion::parse(&version) else {
+            return Self::Unknown;
+        };
+
+        match (operator, wildcard) {
+            ("" | "=", Some(len)) => {
+                let max = version.bump(len);
+                Self::Range(version, max)
+            }
+            ("" | "=", None) => Self::Eq(version),
+            (">", _) => Self::Gt(version),
+            (">=", _) => Self::Ge(version),
+            ("<", _) => Self::Lt(version),
+            ("<=", _) => Self::Le(version),
+            ("~", _) => {
+                let max = version.bump(2);
+                Self::Range(version, max)
+            }
+            ("^", _) => {
+                let max = version.bump(1);
+                Self::Range(version, max)
+            }
+            _ => Self::Unknown,
+        }
+    }
+
+    fn matches(&self, version: &FlexVersion) -> Option<bool> {
+        Some(match self {
+            Self::Any => true,
+            Self::Eq(v) => version == v,
+            Self::Gt(v) => version > v,
+            Self::Ge(v) => version >= v,
+            Self::Lt(v) => version < v,
+            Self::Le(v) => version <= v,
+            Self::Range(min, max) => version >= min && version < max,
+            Self::Unknown => return None,
+        })
+    }
+
+    /// The smallest version this predicate allows, if it has a lower bound
+    fn minimum(&self) -> Option<FlexVersion> {
+        match self {
+            Self::Eq(v) | Self::Gt(v) | Self::Ge(v) | Self::Range(v, _) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    /// The version this predicate compares against, if any
+    fn version(&self) -> Option<&FlexVersion> {
+        match self {
+            Self::Eq(v) | Self::Gt(v) | Self::Ge(v) | Self::Lt(v) | Self::Le(v) => Some(v),
+            Self::Range(v, _) => Some(v),
+            Self::Any | Self::Unknown => None,
+        }
+    }
+}
+
+/// A version requirement as written in `quilt.mod.json` or `fabric.mod.json`
+#[derive(Debug, Clone)]
+pub enum VersionRange {
+    /// Matches if any of the ranges match
+    Any(Vec<VersionRange>),
+    /// Matches if all of the ranges match
+    All(Vec<VersionRange>),
+    Predicate(String),
+}
+
+impl VersionRange {
+    /// Parses space separated predicates, all of which must match
+    pub fn parse(s: &str) -> Self {
+        let mut predicates = s
+            .split_whitespace()
+            .map(|p| Self::Predicate(p.to_owned()))
+            .collect::<Vec<_>>();
+        match predicates.len() {
+            0 => Self::Predicate("*".into()),
+            1 => predicates.remove(0),
+            _ => Self::All(predicates),
+        }
+    }
+
+    /// Whether `version` satisfies this range,
+    /// or `None` if that cannot be determined because a version is not comparable
+    pub fn matches(&self, version: &str) -> Option<bool> {
+        let Some(version) = FlexVersion::parse(version) else {
+            return self.is_any().then_some(true);
+        };
+        self.matches_version(&version)
+    }
+
+    fn matches_version(&self, version: &FlexVersion) -> Option<bool> {
+        match self {
+            Self::Any(ranges) => {
+                let results = ranges.iter().map(|r| r.matches_version(version));
+                let mut unknown = false;
+                for result in results {
+                    match result {
+                        Some(true) => return Some(true),
+                        Some(false) => {}
+                        None => unknown = true,
+                    }
+                }
+                (!unknown).then_some(false)
+            }
+            Self::All(ranges) => {
+                let results = ranges.iter().map(|r| r.matches_version(version));
+                let mut unknown = false;
+                for result in results {
+                    match result {
+                        Some(true) => {}
+                        Some(false) => return Some(false),
+                        None => unknown = true,
+                    }
+                }
+                (!unknown).then_some(true)
+            }
+            Self::Predicate(p) => Predicate::parse(p).matches(version),
+        }
+    }
+
+    /// The smallest version that can satisfy this range, if it has a lower bound
+    pub fn minimum(&self) -> Option<FlexVersion> {
+        match self {
+            Self::Any(ranges) => ranges
+                .iter()
+                .map(Self::minimum)
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .min(),
+            Self::All(ranges) => ranges.iter().filter_map(Self::minimum).max(),
+            Self::Predicate(p) => Predicate::parse(p).minimum(),
+        }
+    }
+
+    /// The first version the range compares against, to point at nearby versions when none match
+    pub fn bound(&self) -> Option<FlexVersion> {
+        match self {
+            Self::Any(ranges) | Self::All(ranges) => ranges.iter().find_map(Self::bound),
+            Self::Predicate(p) => Predicate::parse(p).version().cloned(),
+        }
+    }
+
+    fn is_any(&self) -> bool {
+        match self {
+            Self::Any(ranges) => ranges.iter().any(Self::is_any),
+            Self::All(ranges) => ranges.iter().all(Self::is_any),
+            Self::Predicate(p) => matches!(Predicate::parse(p), Predicate::Any),
+        }
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any(ranges) => {
+                let ranges = ranges.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", ranges.join(" || "))
+            }
+            Self::All(ranges) => {
+                let ranges = ranges.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", ranges.join(" "))
+            }
+            Self::Predicate(p) => write!(f, "{p}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> FlexVersion {
+        FlexVersion::parse(s).unwrap()
+    }
+
+    fn matches(predicate: &str, v: &str) -> Option<bool> {
+        Predicate::parse(predicate).matches(&version(v))
+    }
+
+    #[test]
+    fn missing_components_are_zero() {
+        assert_eq!(version("1.20"), version("1.20.0"));
+        assert!(version("0.19.0-beta.3") < version("0.19.0"));
+        assert!(version("0.19.0-beta.3") < version("0.19.0-beta.10"));
+    }
+
+    #[test]
+    fn tilde_allows_patch_updates() {
+        assert_eq!(matches("~0.19.2", "0.19.2"), Some(true));
+        assert_eq!(matches("~0.19.2", "0.19.9"), Some(true));
+        assert_eq!(matches("~0.19.2", "0.19.1"), Some(false));
+        assert_eq!(matches("~0.19.2", "0.20.0"), Some(false));
+    }
+
+    #[test]
+    fn caret_allows_minor_updates() {
+        assert_eq!(matches("^1.2.3", "1.9.0"), Some(true));
+        assert_eq!(matches("^1.2.3", "1.2.2"), Some(false));
+        assert_eq!(matches("^1.2.3", "2.0.0"), Some(false));
+    }
+
+    #[test]
+    fn wildcards_cover_a_series() {
+        assert_eq!(matches("1.20.x", "1.20"), Some(true));
+        assert_eq!(matches("1.20.x", "1.20.4"), Some(true));
+        assert_eq!(matches("1.20.x", "1.21"), Some(false));
+        assert_eq!(matches("1.20.x", "1.19.4"), Some(false));
+        assert!(matches!(Predicate::parse("*"), Predicate::Any));
+    }
+
+    #[test]
+    fn space_separated_predicates_must_all_match() {
+        let range = VersionRange::parse(">=1.19 <1.20");
+
+        assert_eq!(range.matches("1.19.4"), Some(true));
+        assert_eq!(range.matches("1.20"), Some(false));
+        assert_eq!(range.matches("1.18.2"), Some(false));
+    }
+
+    #[test]
+    fn snapshots_cannot_be_compared() {
+        assert!(matches!(Predicate::parse(">=23w13a"), Predicate::Unknown));
+        assert_eq!(VersionRange::parse(">=1.19").matches("23w13a"), None);
+        assert_eq!(VersionRange::parse("*").matches("23w13a"), Some(true));
+
+        let any = VersionRange::Any(vec![
+            VersionRange::parse("1.19.x"),
+            VersionRange::parse(">=23w13a"),
+        ]);
+        assert_eq!(any.matches("1.20"), None);
+        assert_eq!(any.matches("1.19.2"), Some(true));
+    }
+
+    #[test]
+    fn minimum_is_the_lowest_allowed_version() {
+        let minimum = |s: &str| VersionRange::parse(s).minimum().map(|v| v.to_string());
+
+        assert_eq!(minimum(">=0.19.1"), Some("0.19.1".into()));
+        assert_eq!(minimum("~0.18.3"), Some("0.18.3".into()));
+        assert_eq!(minimum(">=0.17 >=0.18.2 <1"), Some("0.18.2".into()));
+        assert_eq!(minimum("<0.19"), None);
+        assert_eq!(minimum("*"), None);
+
+        let any = VersionRange::Any(vec![
+            VersionRange::parse(">=0.19"),
+            VersionRange::parse(">=0.18"),
+        ]);
+        assert_eq!(any.minimum().map(|v| v.to_string()), Some("0.18".into()));
+    }
+}
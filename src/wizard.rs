@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input, MultiSelect, Select};
+use quilt_installer::{
+    fetch_compatible_loader_versions, fetch_minecraft_versions, Installation, LoaderChannel,
+};
+use reqwest::Client;
+
+use crate::choices::Choices;
+
+/// How many versions are shown at once, more can be found by typing or scrolling
+const VISIBLE_VERSIONS: usize = 10;
+
+/// Asks the questions of the GUI in the terminal, then installs
+pub async fn run(client: Client) -> Result<()> {
+    let theme = ColorfulTheme::default();
+    let mut choices = Choices::default();
+    choices.set_minecraft_versions(fetch_minecraft_versions(client.clone()).await?);
+
+    choices.installation_type = match Select::with_theme(&theme)
+        .with_prompt("Installation")
+        .items(&["Client", "Server"])
+        .default(0)
+        .interact()?
+    {
+        0 => Installation::Client,
+        _ => Installation::Server,
+    };
+
+    loop {
+        let toggle = if choices.show_snapshots {
+            "(hide snapshots)"
+        } else {
+            "(show snapshots)"
+        };
+        let versions = choices.visible_minecraft_versions().collect::<Vec<_>>();
+        let selected = versions
+            .iter()
+            .position(|&v| Some(v) == choices.selected_minecraft_version.as_ref());
+        let index = FuzzySelect::with_theme(&theme)
+            .with_prompt("Minecraft version (type to search)")
+            .item(toggle)
+            .items(&versions)
+            .default(selected.map_or(0, |i| i + 1))
+            .max_length(VISIBLE_VERSIONS)
+            .interact()?;
+        match index.checked_sub(1) {
+            Some(i) => {
+                choices.selected_minecraft_version = Some(versions[i].clone());
+                break;
+            }
+            None => choices.set_show_snapshots(!choices.show_snapshots),
+        }
+    }
+    if let Some(minecraft_version) = &choices.selected_minecraft_version {
+        let versions =
+            fetch_compatible_loader_versions(client.clone(), &minecraft_version.version).await?;
+        choices.set_loader_versions(versions);
+    }
+
+    loop {
+        let toggle = format!("(channel: {}, change)", choices.loader_channel);
+        let versions = choices.visible_loader_versions().collect::<Vec<_>>();
+        let selected = versions
+            .iter()
+            .position(|&v| Some(v) == choices.selected_loader_version.as_ref());
+        let index = FuzzySelect::with_theme(&theme)
+            .with_prompt("Loader version (type to search)")
+            .item(toggle)
+            .items(&versions)
+            .default(selected.map_or(0, |i| i + 1))
+            .max_length(VISIBLE_VERSIONS)
+            .interact()?;
+        match index.checked_sub(1) {
+            Some(i) => {
+                choices.selected_loader_version = Some(versions[i].clone());
+                break;
+            }
+            None => {
+                let channels = LoaderChannel::ALL;
+                let current = channels
+                    .iter()
+                    .position(|&c| c == choices.loader_channel)
+                    .unwrap_or(0);
+                let index = Select::with_theme(&theme)
+                    .with_prompt("Least stable loader channel")
+                    .items(&channels)
+                    .default(current)
+                    .interact()?;
+                choices.set_loader_channel(channels[index]);
+            }
+        }
+    }
+
+    let install_dir: String = Input::with_theme(&theme)
+        .with_prompt("Directory")
+        .default(choices.install_dir().display().to_string())
+        .interact_text()?;
+    choices.set_install_dir(PathBuf::from(install_dir));
+
+    let options = match choices.installation_type {
+        Installation::Client => vec![
+            ("Generate profile", choices.generate_profile),
+            ("Download libraries", choices.download_libraries),
+            ("Install Quilted Fabric API", choices.install_qfapi),
+        ],
+        Installation::Server => vec![
+            ("Download server jar", choices.download_server_jar),
+            ("Generate launch script", choices.generate_launch_script),
+            ("Install Quilted Fabric API", choices.install_qfapi),
+        ],
+    };
+    let picked = MultiSelect::with_theme(&theme)
+        .with_prompt("Options (space to toggle)")
+        .items_checked(&options)
+        .interact()?;
+    let enabled = |i| picked.contains(&i);
+    match choices.installation_type {
+        Installation::Client => {
+            choices.generate_profile = enabled(0);
+            choices.download_libraries = enabled(1);
+        }
+        Installation::Server => {
+            choices.download_server_jar = enabled(0);
+            choices.generate_launch_script = enabled(1);
+        }
+    }
+    choices.install_qfapi = enabled(2);
+
+    let command_line = choices.command_line()?;
+    choices.install(client).await?;
+    println!("To install the same without the wizard, run:\n  {command_line}");
+    Ok(())
+}